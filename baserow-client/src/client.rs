@@ -1,9 +1,14 @@
+use crate::file::BaserowFile;
+use crate::schema::{RemoteField, SchemaMismatch, TableSchema, find_mismatches};
+use crate::tracked::{Trackable, Tracked};
 use crate::url_builder::{Error as UrlBuilderError, UrlBuilder};
-use reqwest::Client as ReqwestClient;
+use crate::validation::{Validate, Violation};
 use reqwest::header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderValue};
+use reqwest::multipart::{Form, Part};
+use reqwest::{Client as ReqwestClient, Response};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use snafu::{ensure, OptionExt, ResultExt, Snafu};
+use snafu::{OptionExt, ResultExt, Snafu, ensure};
 
 #[derive(Snafu, Debug)]
 pub enum Error {
@@ -31,23 +36,71 @@ pub enum Error {
     },
     #[snafu(display("Object has no id, cannot update"))]
     NoIdentifier {},
-    #[snafu(display("Server returned status [{status}]: {msg}"))]
-    ResponseStatus {
-        status: String,
-        msg: String,
+    #[snafu(display("Expected exactly one row for id [{id}], but found {count}"))]
+    AmbiguousIdentifier { id: String, count: usize },
+    #[snafu(
+        display(
+            "Row [{row_id}] was changed by someone else since it was read, conflicting fields: {}",
+            fields.join(", ")
+        ),
+        visibility(pub(crate))
+    )]
+    Conflict { row_id: usize, fields: Vec<String> },
+    #[snafu(display(
+        "Object is not valid: {}",
+        violations
+            .iter()
+            .map(|violation| violation.to_string())
+            .collect::<Vec<String>>()
+            .join(", ")
+    ))]
+    Validation { violations: Vec<Violation> },
+    #[snafu(display(
+        "Table [{table_id}] no longer matches the generated code, regenerate it: {}",
+        mismatches
+            .iter()
+            .map(|mismatch| mismatch.to_string())
+            .collect::<Vec<String>>()
+            .join(", ")
+    ))]
+    SchemaMismatch {
+        table_id: usize,
+        mismatches: Vec<SchemaMismatch>,
     },
+    #[snafu(display("File [{name}] has no url, cannot download"))]
+    NoFileUrl { name: String },
+    #[snafu(display("Server returned status [{status}]: {msg}"))]
+    ResponseStatus { status: String, msg: String },
 }
 
 pub struct Client {
     client: ReqwestClient,
+    // Used to download files, which are served from a different host than the api and must not
+    // receive our token
+    file_client: ReqwestClient,
     url_builder: UrlBuilder,
+    validate: bool,
 }
 
+/// Derive `BaserowObject` for structs written by hand, see [`baserow_derive::BaserowObject`].
+pub use baserow_derive::BaserowObject;
+
 pub trait BaserowObject {
     fn get_static_table_id() -> usize;
     fn get_table_id(&self) -> usize;
     fn get_id(&self) -> Identifier;
     fn get_table_id_field(&self) -> String;
+
+    /// Whether the type refers to fields by their names in Baserow instead of `field_<id>`,
+    /// which the client tells Baserow on every request exchanging rows of the type.
+    const USER_FIELD_NAMES: bool = false;
+}
+
+/// Implemented by the write models generated for every table, which only contain the fields
+/// Baserow accepts when creating or updating rows.
+pub trait BaserowWriteObject: Serialize + Validate {
+    /// The model that rows of the same table are read into
+    type Read: BaserowObject;
 }
 
 pub enum Identifier {
@@ -81,6 +134,14 @@ struct IdOnly {
     pub id: usize,
 }
 
+// Rows always contain their internal id, which the generated structs don't expose
+#[derive(Deserialize, Debug)]
+struct WithRowId<T> {
+    pub id: usize,
+    #[serde(flatten)]
+    pub row: T,
+}
+
 impl Client {
     pub fn new(token: &str, base_url: Option<&str>) -> Result<Self, Error> {
         // Build default headers to be included with every request later on
@@ -105,129 +166,422 @@ impl Client {
                 .context(ReqwestSnafu {
                     msg: "build client",
                 })?,
+            file_client: ReqwestClient::builder().build().context(ReqwestSnafu {
+                msg: "build file client",
+            })?,
             url_builder: UrlBuilder::new(base_url).context(UrlBuilderSnafu {})?,
+            validate: false,
         })
     }
 
+    /// Validate objects before creating or updating rows, so that values Baserow would refuse
+    /// fail with [`Error::Validation`] without sending a request.
+    pub fn with_validation(mut self, validate: bool) -> Self {
+        self.validate = validate;
+        self
+    }
+
+    fn ensure_valid<T>(&self, obj: &T) -> Result<(), Error>
+    where
+        T: Validate,
+    {
+        if self.validate {
+            let violations = obj.validate();
+            ensure!(violations.is_empty(), ValidationSnafu { violations });
+        }
+        Ok(())
+    }
+
     pub async fn list<T>(&self) -> Result<Vec<T>, Error>
     where
-        T: BaserowObject + Serialize + DeserializeOwned,
+        T: BaserowObject + DeserializeOwned,
+    {
+        self.list_rows::<T>(T::get_static_table_id(), T::USER_FIELD_NAMES)
+            .await
+    }
+
+    /// Retrieve all rows of a table wrapped in [`Tracked`], to later only save fields that
+    /// were changed.
+    pub async fn list_tracked<T>(&self) -> Result<Vec<Tracked<T>>, Error>
+    where
+        T: Trackable,
+    {
+        Ok(self
+            .list_rows::<WithRowId<T>>(T::get_static_table_id(), T::USER_FIELD_NAMES)
+            .await?
+            .into_iter()
+            .map(|row| Tracked::new(row.id, row.row))
+            .collect())
+    }
+
+    /// Retrieve a single row by its internal Baserow id.
+    pub async fn get<T>(&self, row_id: usize) -> Result<T, Error>
+    where
+        T: BaserowObject + DeserializeOwned,
+    {
+        self.get_row::<T>(T::get_static_table_id(), row_id, T::USER_FIELD_NAMES)
+            .await
+    }
+
+    /// Retrieve a single row by its internal Baserow id wrapped in [`Tracked`], to later only
+    /// save fields that were changed.
+    pub async fn get_tracked<T>(&self, row_id: usize) -> Result<Tracked<T>, Error>
+    where
+        T: Trackable,
+    {
+        let row = self
+            .get_row::<WithRowId<T>>(T::get_static_table_id(), row_id, T::USER_FIELD_NAMES)
+            .await?;
+        Ok(Tracked::new(row.id, row.row))
+    }
+
+    async fn list_rows<R>(&self, table_id: usize, user_field_names: bool) -> Result<Vec<R>, Error>
+    where
+        R: DeserializeOwned,
+    {
+        let mut url = self
+            .url_builder
+            .get_list_records_url(table_id, user_field_names)
+            .context(UrlBuilderSnafu)?
+            .to_string();
+        let mut rows = Vec::new();
+
+        // Results are paginated, keep following the link to the next page until we have all rows
+        loop {
+            let response = self
+                .client
+                .get(&url)
+                .send()
+                .await
+                .context(ReqwestWithUrlSnafu {
+                    msg: "send list request",
+                    url: &url,
+                })?;
+
+            let page = ensure_success(response)
+                .await?
+                .json::<SearchResult<R>>()
+                .await
+                .context(ReqwestWithUrlSnafu {
+                    msg: "deserialize list response",
+                    url: &url,
+                })?;
+            rows.extend(page.results);
+
+            match page.next {
+                Some(next) => url = next,
+                None => break,
+            }
+        }
+
+        Ok(rows)
+    }
+
+    async fn get_row<R>(
+        &self,
+        table_id: usize,
+        row_id: usize,
+        user_field_names: bool,
+    ) -> Result<R, Error>
+    where
+        R: DeserializeOwned,
     {
         let url = self
             .url_builder
-            .get_list_records_url(T::get_static_table_id())
+            .get_read_record_url(table_id, row_id, user_field_names)
             .context(UrlBuilderSnafu)?;
-        println!("Calling url: {}", url);
+
         let response = self
             .client
             .get(url.as_ref())
             .send()
             .await
             .context(ReqwestWithUrlSnafu {
-                msg: "send list request",
+                msg: "send get request",
                 url: url.as_ref(),
             })?;
-        ensure!(response.status().is_success(), ResponseStatusSnafu {status: response.status().to_string(),msg: response.text().await.unwrap()});
 
-        println!("{}", response.text().await.unwrap());
-        Ok(Vec::new())
-            /*Ok(response
-                .json::<SearchResult<T>>()
-                .await
-                .context(ReqwestSnafu {
-                    msg: "deserialize list response",
-                })?
-                .results)
-
-             */
-            }
+        ensure_success(response)
+            .await?
+            .json::<R>()
+            .await
+            .context(ReqwestWithUrlSnafu {
+                msg: "deserialize get response",
+                url: url.as_ref(),
+            })
+    }
 
     pub async fn create<T>(&self, obj: &T) -> Result<(), Error>
     where
-        T: BaserowObject + Serialize,
+        T: BaserowWriteObject,
     {
+        self.ensure_valid(obj)?;
+        let table_id = T::Read::get_static_table_id();
         let url = self
             .url_builder
-            .get_create_record_url(obj.get_table_id())
+            .get_create_record_url(table_id, T::Read::USER_FIELD_NAMES)
             .context(UrlBuilderSnafu {})?;
 
-        let request = self
+        let response = self
             .client
             .post(url.as_ref())
             .header(CONTENT_TYPE, "application/json")
             .body(serde_json::to_string(obj).context(SerializeRequestSnafu {
-                msg: obj.get_table_id().to_string(),
+                msg: table_id.to_string(),
             })?)
-            .build()
-            .context(ReqwestSnafu {
-                msg: "build create request",
+            .send()
+            .await
+            .context(ReqwestWithUrlSnafu {
+                msg: "send create request",
+                url: url.as_ref(),
             })?;
+        ensure_success(response).await?;
+        Ok(())
+    }
+
+    /// Update the row identified by the primary field of `obj` with all values of `obj`.
+    ///
+    /// Fields that are `None` in `obj` are cleared in Baserow, use [`Client::patch`] to only
+    /// update specific fields.
+    pub async fn update<T>(&self, obj: &T) -> Result<(), Error>
+    where
+        T: BaserowObject + BaserowWriteObject,
+    {
+        let row_id = self.find_row_id(obj).await?;
+        self.update_row(row_id, obj).await
+    }
+
+    /// Update the row identified by the primary field of `row` with the fields that were set in
+    /// `patch`, all other fields are left untouched.
+    pub async fn patch<R, P>(&self, row: &R, patch: &P) -> Result<(), Error>
+    where
+        R: BaserowObject,
+        P: BaserowWriteObject<Read = R>,
+    {
+        let row_id = self.find_row_id(row).await?;
+        self.update_row(row_id, patch).await
+    }
+
+    /// Update the row with the internal Baserow id `row_id` with the serialized fields of `obj`.
+    pub async fn update_row<T>(&self, row_id: usize, obj: &T) -> Result<(), Error>
+    where
+        T: BaserowWriteObject,
+    {
+        self.ensure_valid(obj)?;
+        let table_id = T::Read::get_static_table_id();
+        let url = self
+            .url_builder
+            .get_update_record_url(table_id, row_id, T::Read::USER_FIELD_NAMES)
+            .context(UrlBuilderSnafu)?;
 
-        println!("Request\n{:?}", request);
         let response = self
             .client
-            .execute(request)
+            .patch(url.as_ref())
+            .header(CONTENT_TYPE, "application/json")
+            .body(serde_json::to_string(obj).context(SerializeRequestSnafu {
+                msg: table_id.to_string(),
+            })?)
+            .send()
             .await
             .context(ReqwestWithUrlSnafu {
-                msg: "send create request",
+                msg: "send update request",
                 url: url.as_ref(),
             })?;
-
-        println!("{:?}", response);
+        ensure_success(response).await?;
         Ok(())
     }
 
-    pub async fn update<T>(&self, obj: &T) -> Result<(), Error>
+    // Baserow doesn't enforce the primary field to be unique, so we need to look up the internal
+    // id of the row and make sure that the primary field actually identifies a single row
+    async fn find_row_id<R>(&self, row: &R) -> Result<usize, Error>
     where
-        T: BaserowObject + Serialize,
+        R: BaserowObject,
     {
-        // Need to find the rowid for the object first
-        let id: String = obj.get_id().get_string().context(NoIdentifierSnafu)?;
+        let id: String = row.get_id().get_string().context(NoIdentifierSnafu)?;
 
         let url = self
             .url_builder
-            .get_find_record_url(obj.get_table_id(), &obj.get_table_id_field(), &id)
+            .get_find_record_url(
+                row.get_table_id(),
+                &row.get_table_id_field(),
+                &id,
+                R::USER_FIELD_NAMES,
+            )
             .context(UrlBuilderSnafu)?;
 
-        let search_result = self
+        let response = self
             .client
             .get(url.as_ref())
             .send()
             .await
             .context(ReqwestWithUrlSnafu {
-                msg: "send update request",
+                msg: "send search request",
                 url: url.as_ref(),
-            })?
+            })?;
+        let search_result = ensure_success(response)
+            .await?
             .json::<SearchResult<IdOnly>>()
             .await
             .context(ReqwestSnafu {
                 msg: "deserialize search response",
             })?;
 
-        if !search_result.count.eq(&1) {
-            panic!("Should only have found one object for primary id!");
-        }
+        ensure!(
+            search_result.count.eq(&1),
+            AmbiguousIdentifierSnafu {
+                id,
+                count: search_result.count
+            }
+        );
 
-        let id = search_result.results.first().unwrap().id;
+        search_result
+            .results
+            .first()
+            .map(|row| row.id)
+            .context(AmbiguousIdentifierSnafu { id, count: 0usize })
+    }
 
+    /// Check that the fields of the table of `T` in Baserow still match the fields `T` was
+    /// generated for, fails with [`Error::SchemaMismatch`] listing all fields that were deleted,
+    /// changed their type or a setting the type depends on, or got new select options. Renamed
+    /// fields are listed as well if `T` refers to fields by their names.
+    ///
+    /// Meant to be called on startup, so that changes in Baserow are noticed right away instead
+    /// of when the first row fails to deserialize.
+    pub async fn verify_schema<T>(&self) -> Result<(), Error>
+    where
+        T: TableSchema,
+    {
+        let table_id = T::get_static_table_id();
         let url = self
             .url_builder
-            .get_update_record_url(obj.get_table_id(), id)
+            .get_table_fields_url(table_id)
             .context(UrlBuilderSnafu)?;
 
         let response = self
             .client
-            .patch(url.as_ref())
-            .header(CONTENT_TYPE, "application/json")
-            .body(serde_json::to_string(obj).unwrap())
+            .get(url.as_ref())
             .send()
             .await
             .context(ReqwestWithUrlSnafu {
-                msg: "send update request",
+                msg: "send list fields request",
+                url: url.as_ref(),
+            })?;
+        let remote_fields = ensure_success(response)
+            .await?
+            .json::<Vec<RemoteField>>()
+            .await
+            .context(ReqwestWithUrlSnafu {
+                msg: "deserialize list fields response",
                 url: url.as_ref(),
             })?;
-        println!("{:?}", response);
-        println!("{:?}", response.text().await.unwrap());
+
+        let mismatches = find_mismatches(T::get_fields(), &remote_fields, T::USER_FIELD_NAMES);
+        ensure!(
+            mismatches.is_empty(),
+            SchemaMismatchSnafu {
+                table_id,
+                mismatches
+            }
+        );
         Ok(())
     }
+
+    /// Upload a file to Baserow, the returned [`BaserowFile`] can then be added to a file field
+    /// of a row.
+    pub async fn upload_file(&self, bytes: Vec<u8>, name: &str) -> Result<BaserowFile, Error> {
+        let url = self
+            .url_builder
+            .get_upload_file_url()
+            .context(UrlBuilderSnafu)?;
+
+        let form = Form::new().part("file", Part::bytes(bytes).file_name(name.to_string()));
+        let response = self
+            .client
+            .post(url.as_ref())
+            .multipart(form)
+            .send()
+            .await
+            .context(ReqwestWithUrlSnafu {
+                msg: "send upload file request",
+                url: url.as_ref(),
+            })?;
+
+        ensure_success(response)
+            .await?
+            .json::<BaserowFile>()
+            .await
+            .context(ReqwestSnafu {
+                msg: "deserialize upload file response",
+            })
+    }
+
+    /// Have Baserow download the file at `file_url` and store it, the returned [`BaserowFile`]
+    /// can then be added to a file field of a row.
+    pub async fn upload_file_via_url(&self, file_url: &str) -> Result<BaserowFile, Error> {
+        let url = self
+            .url_builder
+            .get_upload_file_via_url_url()
+            .context(UrlBuilderSnafu)?;
+
+        let response = self
+            .client
+            .post(url.as_ref())
+            .json(&serde_json::json!({ "url": file_url }))
+            .send()
+            .await
+            .context(ReqwestWithUrlSnafu {
+                msg: "send upload file via url request",
+                url: url.as_ref(),
+            })?;
+
+        ensure_success(response)
+            .await?
+            .json::<BaserowFile>()
+            .await
+            .context(ReqwestSnafu {
+                msg: "deserialize upload file via url response",
+            })
+    }
+
+    /// Download the content of a file that was retrieved from a file field.
+    pub async fn download_file(&self, file: &BaserowFile) -> Result<Vec<u8>, Error> {
+        let url = file
+            .url
+            .as_ref()
+            .context(NoFileUrlSnafu { name: &file.name })?;
+
+        let response = self
+            .file_client
+            .get(url)
+            .send()
+            .await
+            .context(ReqwestWithUrlSnafu {
+                msg: "send download file request",
+                url,
+            })?;
+
+        Ok(ensure_success(response)
+            .await?
+            .bytes()
+            .await
+            .context(ReqwestWithUrlSnafu {
+                msg: "read downloaded file",
+                url,
+            })?
+            .to_vec())
+    }
+}
+
+async fn ensure_success(response: Response) -> Result<Response, Error> {
+    let status = response.status();
+    if status.is_success() {
+        Ok(response)
+    } else {
+        ResponseStatusSnafu {
+            status: status.to_string(),
+            msg: response.text().await.unwrap_or_default(),
+        }
+        .fail()
+    }
 }
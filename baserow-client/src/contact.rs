@@ -0,0 +1,204 @@
+use crate::validation::{is_valid_email, is_valid_phone_number, is_valid_url};
+use serde::{Deserialize, Serialize};
+use snafu::{Snafu, ensure};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Snafu, Debug)]
+pub enum Error {
+    #[snafu(display("[{value}] is not a valid email address"))]
+    InvalidEmail { value: String },
+    #[snafu(display("[{value}] is not a valid phone number"))]
+    InvalidPhoneNumber { value: String },
+    #[snafu(display("[{value}] is not a valid url"))]
+    InvalidUrl { value: String },
+}
+
+/// Value of an email field, can only be created from a valid email address.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(try_from = "String", into = "String")]
+pub struct Email(String);
+
+impl Email {
+    pub fn new(value: &str) -> Result<Self, Error> {
+        ensure!(
+            is_valid_email(value),
+            InvalidEmailSnafu {
+                value: value.to_string()
+            }
+        );
+        Ok(Self(value.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Value of a phone number field, can only be created from characters Baserow accepts for
+/// phone numbers.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(try_from = "String", into = "String")]
+pub struct PhoneNumber(String);
+
+impl PhoneNumber {
+    pub fn new(value: &str) -> Result<Self, Error> {
+        ensure!(
+            is_valid_phone_number(value),
+            InvalidPhoneNumberSnafu {
+                value: value.to_string()
+            }
+        );
+        Ok(Self(value.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Value of a url field, can only be created from a url Baserow accepts.
+///
+/// Keeps the url as it was entered, urls without a scheme like `www.example.com` stay that way
+/// when written back. [`Url::to_url`] parses it, adding `https://` where the scheme is missing.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(try_from = "String", into = "String")]
+pub struct Url(String);
+
+impl Url {
+    pub fn new(value: &str) -> Result<Self, Error> {
+        ensure!(
+            is_valid_url(value),
+            InvalidUrlSnafu {
+                value: value.to_string()
+            }
+        );
+        Ok(Self(value.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn to_url(&self) -> url::Url {
+        // Both unwraps are okay, the value was validated by parsing it the same way
+        url::Url::parse(&self.0)
+            .ok()
+            .filter(|url| url.has_host())
+            .unwrap_or_else(|| url::Url::parse(&format!("https://{}", self.0)).unwrap())
+    }
+}
+
+impl FromStr for Email {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::new(value)
+    }
+}
+
+impl TryFrom<String> for Email {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::new(&value)
+    }
+}
+
+impl From<Email> for String {
+    fn from(value: Email) -> Self {
+        value.0
+    }
+}
+
+impl fmt::Display for Email {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for PhoneNumber {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::new(value)
+    }
+}
+
+impl TryFrom<String> for PhoneNumber {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::new(&value)
+    }
+}
+
+impl From<PhoneNumber> for String {
+    fn from(value: PhoneNumber) -> Self {
+        value.0
+    }
+}
+
+impl fmt::Display for PhoneNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for Url {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::new(value)
+    }
+}
+
+impl TryFrom<String> for Url {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::new(&value)
+    }
+}
+
+impl From<Url> for String {
+    fn from(value: Url) -> Self {
+        value.0
+    }
+}
+
+impl fmt::Display for Url {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::contact::{Email, PhoneNumber, Url};
+
+    #[test]
+    fn test_deserialize_contact() {
+        let email = serde_json::from_str::<Email>(r#""jane.doe@example.com""#).unwrap();
+        assert_eq!(email.as_str(), "jane.doe@example.com");
+        assert!(serde_json::from_str::<Email>(r#""Jane Doe""#).is_err());
+
+        let phone_number = serde_json::from_str::<PhoneNumber>(r#""+49 170 1234567""#).unwrap();
+        assert_eq!(
+            serde_json::to_string(&phone_number).unwrap(),
+            r#""+49 170 1234567""#
+        );
+        assert!(serde_json::from_str::<PhoneNumber>(r#""call me""#).is_err());
+    }
+
+    #[test]
+    fn test_url_keeps_original() {
+        let url = serde_json::from_str::<Url>(r#""www.example.com""#).unwrap();
+        assert_eq!(serde_json::to_string(&url).unwrap(), r#""www.example.com""#);
+        assert_eq!(url.to_url().as_str(), "https://www.example.com/");
+
+        let url = Url::new("http://example.com/docs").unwrap();
+        assert_eq!(url.to_url().as_str(), "http://example.com/docs");
+        assert!(serde_json::from_str::<Url>(r#""not a url""#).is_err());
+    }
+}
@@ -0,0 +1,204 @@
+use chrono::TimeDelta;
+use serde::de::{self, Visitor};
+use serde::{Deserializer, Serializer};
+use std::fmt;
+
+/// Format a duration the way Baserow displays it for the given `duration_format` of a field.
+///
+/// Baserow rounds durations to the precision of the format, so `h:mm` shows 90 seconds as `0:02`.
+/// Negative durations get a leading `-`. Unknown formats fall back to the number of seconds.
+pub fn format_duration(duration: &TimeDelta, format: &str) -> String {
+    let sign = if *duration < TimeDelta::zero() {
+        "-"
+    } else {
+        ""
+    };
+    format!(
+        "{}{}",
+        sign,
+        format_seconds(as_seconds(&duration.abs()), format)
+    )
+}
+
+fn format_seconds(total: f64, format: &str) -> String {
+    let seconds = total.round() as u64;
+    let minutes = (total / 60.0).round() as u64;
+
+    match format {
+        "h:mm" => format!("{}:{:02}", minutes / 60, minutes % 60),
+        "h:mm:ss" => format!(
+            "{}:{:02}:{:02}",
+            seconds / 3600,
+            seconds % 3600 / 60,
+            seconds % 60
+        ),
+        "h:mm:ss.s" | "h:mm:ss.ss" | "h:mm:ss.sss" => {
+            let precision = format.len() - "h:mm:ss.".len();
+            let unit = 10u64.pow(precision as u32);
+            let scaled = (total * unit as f64).round() as u64;
+            let seconds = scaled / unit;
+            format!(
+                "{}:{:02}:{:02}.{:0width$}",
+                seconds / 3600,
+                seconds % 3600 / 60,
+                seconds % 60,
+                scaled % unit,
+                width = precision
+            )
+        }
+        "d h" => {
+            let hours = (total / 3600.0).round() as u64;
+            format!("{}d {}h", hours / 24, hours % 24)
+        }
+        "d h:mm" => format!(
+            "{}d {}:{:02}",
+            minutes / 1440,
+            minutes % 1440 / 60,
+            minutes % 60
+        ),
+        "d h:mm:ss" => format!(
+            "{}d {}:{:02}:{:02}",
+            seconds / 86400,
+            seconds % 86400 / 3600,
+            seconds % 3600 / 60,
+            seconds % 60
+        ),
+        "d h mm" => format!(
+            "{}d {}h {}m",
+            minutes / 1440,
+            minutes % 1440 / 60,
+            minutes % 60
+        ),
+        "d h mm ss" => format!(
+            "{}d {}h {}m {}s",
+            seconds / 86400,
+            seconds % 86400 / 3600,
+            seconds % 3600 / 60,
+            seconds % 60
+        ),
+        _ => format!("{}s", total),
+    }
+}
+
+// Baserow stores durations with up to millisecond precision
+fn as_seconds(duration: &TimeDelta) -> f64 {
+    duration.num_milliseconds() as f64 / 1000.0
+}
+
+fn from_seconds<E: de::Error>(seconds: f64) -> Result<TimeDelta, E> {
+    if !seconds.is_finite() {
+        return Err(de::Error::custom(format!("invalid duration [{}]", seconds)));
+    }
+    TimeDelta::try_milliseconds((seconds * 1000.0).round() as i64)
+        .ok_or_else(|| de::Error::custom(format!("duration out of range [{}]", seconds)))
+}
+
+/// Deserializes the number of seconds Baserow sends for duration fields, which can be negative
+/// for formulas like the difference of two dates.
+pub fn duration_or_null<'de, D>(deserializer: D) -> Result<Option<TimeDelta>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct DurationOrNull;
+
+    impl<'de> Visitor<'de> for DurationOrNull {
+        type Value = Option<TimeDelta>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("number of seconds or null")
+        }
+
+        fn visit_f64<E>(self, value: f64) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            from_seconds(value).map(Some)
+        }
+
+        fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            i64::try_from(value)
+                .ok()
+                .and_then(TimeDelta::try_seconds)
+                .map(Some)
+                .ok_or_else(|| de::Error::custom(format!("duration out of range [{}]", value)))
+        }
+
+        fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            TimeDelta::try_seconds(value)
+                .map(Some)
+                .ok_or_else(|| de::Error::custom(format!("duration out of range [{}]", value)))
+        }
+
+        fn visit_unit<E>(self) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            Ok(None)
+        }
+    }
+
+    deserializer.deserialize_any(DurationOrNull)
+}
+
+/// Serializes a duration as the number of seconds, which is what Baserow expects.
+pub fn duration_as_seconds<S>(value: &Option<TimeDelta>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match value {
+        Some(duration) => serializer.serialize_f64(as_seconds(duration)),
+        None => serializer.serialize_none(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::duration::{duration_as_seconds, duration_or_null, format_duration};
+    use chrono::TimeDelta;
+    use serde::{Deserialize, Serialize};
+
+    #[test]
+    fn test_format_duration() {
+        let duration = TimeDelta::milliseconds(93_784_250);
+
+        assert_eq!(format_duration(&duration, "h:mm"), "26:03");
+        assert_eq!(format_duration(&duration, "h:mm:ss"), "26:03:04");
+        assert_eq!(format_duration(&duration, "h:mm:ss.ss"), "26:03:04.25");
+        assert_eq!(format_duration(&duration, "d h"), "1d 2h");
+        assert_eq!(format_duration(&duration, "d h:mm"), "1d 2:03");
+        assert_eq!(format_duration(&duration, "d h:mm:ss"), "1d 2:03:04");
+        assert_eq!(format_duration(&duration, "d h mm"), "1d 2h 3m");
+        assert_eq!(format_duration(&duration, "d h mm ss"), "1d 2h 3m 4s");
+        assert_eq!(format_duration(&-duration, "h:mm:ss"), "-26:03:04");
+    }
+
+    #[derive(Serialize, Deserialize, Debug)]
+    struct Row {
+        #[serde(
+            deserialize_with = "duration_or_null",
+            serialize_with = "duration_as_seconds"
+        )]
+        duration: Option<TimeDelta>,
+    }
+
+    #[test]
+    fn test_duration_serde() {
+        let row = serde_json::from_str::<Row>(r#"{"duration": -5400.5}"#).unwrap();
+        assert_eq!(row.duration, Some(-TimeDelta::milliseconds(5_400_500)));
+        assert_eq!(
+            serde_json::to_string(&row).unwrap(),
+            r#"{"duration":-5400.5}"#
+        );
+
+        let row = serde_json::from_str::<Row>(r#"{"duration": -60}"#).unwrap();
+        assert_eq!(row.duration, Some(TimeDelta::minutes(-1)));
+        let row = serde_json::from_str::<Row>(r#"{"duration": null}"#).unwrap();
+        assert_eq!(row.duration, None);
+    }
+}
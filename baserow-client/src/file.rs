@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A file stored in Baserow, as it is returned for cells of file fields and by the upload
+/// endpoints.
+///
+/// When writing a file field Baserow only looks at `name` (the internal name assigned at upload
+/// time) and `visible_name`, so a file that was returned by [`crate::client::Client::upload_file`]
+/// can be put into a row as is.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BaserowFile {
+    pub name: String,
+    // The upload endpoints call this `original_name`, rows call it `visible_name`
+    #[serde(alias = "original_name", skip_serializing_if = "Option::is_none")]
+    pub visible_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnails: Option<HashMap<String, Thumbnail>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_image: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_width: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_height: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uploaded_at: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Thumbnail {
+    pub url: String,
+    pub width: Option<usize>,
+    pub height: Option<usize>,
+}
+
+impl BaserowFile {
+    /// Reference an already uploaded file by its internal name, optionally giving it a different
+    /// name to display in the row.
+    pub fn new(name: &str, visible_name: Option<&str>) -> Self {
+        Self {
+            name: name.to_string(),
+            visible_name: visible_name.map(str::to_string),
+            url: None,
+            thumbnails: None,
+            mime_type: None,
+            size: None,
+            is_image: None,
+            image_width: None,
+            image_height: None,
+            uploaded_at: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::file::BaserowFile;
+
+    #[test]
+    fn test_deserialize_upload_response() {
+        let response = r#"{
+            "url": "https://files.baserow.io/user_files/abc_report.pdf",
+            "thumbnails": null,
+            "name": "abc_report.pdf",
+            "size": 2048,
+            "mime_type": "application/pdf",
+            "is_image": false,
+            "image_width": null,
+            "image_height": null,
+            "uploaded_at": "2025-06-01T12:00:00.000000Z",
+            "original_name": "report.pdf"
+        }"#;
+
+        let file: BaserowFile = serde_json::from_str(response).unwrap();
+        assert_eq!(file.visible_name.as_deref(), Some("report.pdf"));
+        assert_eq!(file.size, Some(2048));
+    }
+
+    #[test]
+    fn test_serialize_reference() {
+        let file = BaserowFile::new("abc_report.pdf", Some("report.pdf"));
+        assert_eq!(
+            serde_json::to_string(&file).unwrap(),
+            r#"{"name":"abc_report.pdf","visible_name":"report.pdf"}"#
+        );
+    }
+}
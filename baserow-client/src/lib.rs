@@ -1,3 +1,9 @@
 pub mod client;
+pub mod contact;
+pub mod duration;
+pub mod file;
+pub mod rich_text;
+pub mod schema;
+pub mod tracked;
 mod url_builder;
-
+pub mod validation;
@@ -0,0 +1,106 @@
+use pulldown_cmark::{Event, Options, Parser, TagEnd, html};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Value of a long text field with rich text formatting enabled.
+///
+/// Baserow exchanges rich text as Markdown, which is kept as is so that it can be written back
+/// unchanged.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[serde(transparent)]
+pub struct RichText(String);
+
+impl RichText {
+    pub fn new(markdown: &str) -> Self {
+        Self(markdown.to_string())
+    }
+
+    pub fn as_markdown(&self) -> &str {
+        &self.0
+    }
+
+    /// The text as HTML. HTML in the Markdown is escaped, so that whoever can edit the field
+    /// can't inject markup or scripts.
+    pub fn to_html(&self) -> String {
+        let events = self.parser().map(|event| match event {
+            Event::Html(value) | Event::InlineHtml(value) => Event::Text(value),
+            event => event,
+        });
+        let mut html = String::new();
+        html::push_html(&mut html, events);
+        html
+    }
+
+    /// The text without any formatting, paragraphs and list items are separated by line breaks.
+    pub fn to_plain_text(&self) -> String {
+        let mut text = String::new();
+        for event in self.parser() {
+            match event {
+                Event::Text(value) | Event::Code(value) => text.push_str(&value),
+                Event::SoftBreak | Event::HardBreak => text.push('\n'),
+                Event::End(
+                    TagEnd::Paragraph | TagEnd::Heading(_) | TagEnd::CodeBlock | TagEnd::Item,
+                ) if !text.ends_with('\n') => text.push('\n'),
+                _ => {}
+            }
+        }
+        text.trim_end().to_string()
+    }
+
+    fn parser(&self) -> Parser<'_> {
+        // Baserow's editor supports strikethrough and task lists on top of plain Markdown
+        Parser::new_ext(
+            &self.0,
+            Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS,
+        )
+    }
+}
+
+impl From<String> for RichText {
+    fn from(markdown: String) -> Self {
+        Self(markdown)
+    }
+}
+
+impl From<&str> for RichText {
+    fn from(markdown: &str) -> Self {
+        Self::new(markdown)
+    }
+}
+
+impl fmt::Display for RichText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rich_text::RichText;
+
+    #[test]
+    fn test_rich_text_conversions() {
+        let text = RichText::new("# Kickoff\n\nBring **coffee** and ~~cake~~\n\n- agenda\n- notes");
+
+        assert_eq!(
+            text.to_plain_text(),
+            "Kickoff\nBring coffee and cake\nagenda\nnotes"
+        );
+        assert_eq!(
+            text.to_html(),
+            "<h1>Kickoff</h1>\n<p>Bring <strong>coffee</strong> and <del>cake</del></p>\n<ul>\n<li>agenda</li>\n<li>notes</li>\n</ul>\n"
+        );
+        assert_eq!(
+            RichText::new("Hi <script>alert(1)</script>").to_html(),
+            "<p>Hi &lt;script&gt;alert(1)&lt;/script&gt;</p>\n"
+        );
+        assert_eq!(
+            RichText::new("<div onclick=\"steal()\">\n\nclick</div>").to_html(),
+            "&lt;div onclick=\"steal()\"&gt;\n<p>click&lt;/div&gt;</p>\n"
+        );
+        assert_eq!(
+            serde_json::to_string(&text).unwrap(),
+            serde_json::to_string(text.as_markdown()).unwrap()
+        );
+    }
+}
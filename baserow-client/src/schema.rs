@@ -0,0 +1,468 @@
+use crate::client::BaserowObject;
+use serde::Deserialize;
+use std::fmt;
+
+/// Id of a field in Baserow.
+///
+/// Displays as `field_<id>`, which is how the api refers to fields in rows and filters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FieldId(usize);
+
+impl FieldId {
+    pub const fn new(id: usize) -> Self {
+        Self(id)
+    }
+
+    pub const fn id(&self) -> usize {
+        self.0
+    }
+}
+
+impl fmt::Display for FieldId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "field_{}", self.0)
+    }
+}
+
+/// Description of a field of a generated struct, as it was in Baserow when the code was
+/// generated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldMeta {
+    /// Name of the field in Baserow
+    pub name: &'static str,
+    pub id: FieldId,
+    /// Type of the field as Baserow calls it, like `text` or `single_select`
+    pub field_type: &'static str,
+    pub read_only: bool,
+    pub primary: bool,
+    /// Values of the select options, empty for fields that aren't selects
+    pub options: &'static [&'static str],
+    /// Settings the generated type depends on, named like in the api. `None` for settings that
+    /// the field doesn't have, these aren't checked.
+    pub formula_type: Option<&'static str>,
+    pub array_formula_type: Option<&'static str>,
+    pub number_decimal_places: Option<usize>,
+    pub number_negative: Option<bool>,
+    pub date_include_time: Option<bool>,
+    pub max_value: Option<usize>,
+}
+
+/// Implemented by the generated read models, describes the fields of the table as they were
+/// when the code was generated.
+pub trait TableSchema: BaserowObject {
+    fn get_fields() -> &'static [FieldMeta];
+}
+
+/// A difference between the fields a struct was generated for and the fields of its table in
+/// Baserow, that would make reading or writing rows fail.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaMismatch {
+    DeletedField {
+        name: &'static str,
+        id: FieldId,
+    },
+    /// Only reported for structs that refer to fields by their names
+    RenamedField {
+        name: &'static str,
+        id: FieldId,
+        actual: String,
+    },
+    ChangedType {
+        name: &'static str,
+        id: FieldId,
+        expected: &'static str,
+        actual: String,
+    },
+    ChangedSetting {
+        name: &'static str,
+        id: FieldId,
+        setting: &'static str,
+        expected: String,
+        actual: String,
+    },
+    NewSelectOptions {
+        name: &'static str,
+        id: FieldId,
+        options: Vec<String>,
+    },
+}
+
+impl fmt::Display for SchemaMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaMismatch::DeletedField { name, id } => {
+                write!(f, "field `{}` ({}) was deleted", name, id)
+            }
+            SchemaMismatch::RenamedField { name, id, actual } => {
+                write!(f, "field `{}` ({}) was renamed to `{}`", name, id, actual)
+            }
+            SchemaMismatch::ChangedType {
+                name,
+                id,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "field `{}` ({}) changed its type from `{}` to `{}`",
+                name, id, expected, actual
+            ),
+            SchemaMismatch::ChangedSetting {
+                name,
+                id,
+                setting,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "field `{}` ({}) changed `{}` from `{}` to `{}`",
+                name, id, setting, expected, actual
+            ),
+            SchemaMismatch::NewSelectOptions { name, id, options } => write!(
+                f,
+                "field `{}` ({}) has new options {}",
+                name,
+                id,
+                options
+                    .iter()
+                    .map(|option| format!("`{}`", option))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+// The parts of a field as returned by the api that are needed to detect mismatches
+#[derive(Deserialize, Debug)]
+pub(crate) struct RemoteField {
+    id: usize,
+    name: String,
+    #[serde(rename = "type")]
+    field_type: String,
+    #[serde(default)]
+    select_options: Option<Vec<RemoteSelectOption>>,
+    #[serde(default)]
+    formula_type: Option<String>,
+    #[serde(default)]
+    array_formula_type: Option<String>,
+    #[serde(default)]
+    number_decimal_places: Option<usize>,
+    #[serde(default)]
+    number_negative: Option<bool>,
+    #[serde(default)]
+    date_include_time: Option<bool>,
+    #[serde(default)]
+    max_value: Option<usize>,
+}
+
+impl RemoteField {
+    // Settings as (name, expected, actual), settings without an expected value aren't checked
+    fn settings(&self, field: &FieldMeta) -> [(&'static str, Option<String>, Option<String>); 6] {
+        fn value<T: ToString>(value: Option<T>) -> Option<String> {
+            value.map(|value| value.to_string())
+        }
+
+        [
+            (
+                "formula_type",
+                value(field.formula_type),
+                self.formula_type.clone(),
+            ),
+            (
+                "array_formula_type",
+                value(field.array_formula_type),
+                self.array_formula_type.clone(),
+            ),
+            (
+                "number_decimal_places",
+                value(field.number_decimal_places),
+                value(self.number_decimal_places),
+            ),
+            (
+                "number_negative",
+                value(field.number_negative),
+                value(self.number_negative),
+            ),
+            (
+                "date_include_time",
+                value(field.date_include_time),
+                value(self.date_include_time),
+            ),
+            ("max_value", value(field.max_value), value(self.max_value)),
+        ]
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct RemoteSelectOption {
+    value: String,
+}
+
+/// Compare the fields the code was generated for with the fields Baserow currently has.
+///
+/// Fields that were added in Baserow are ignored, since the generated code simply doesn't read
+/// them, as are select options that were removed. Renamed fields only matter when the rows are
+/// exchanged with `user_field_names`, otherwise fields are referred to by their ids.
+pub(crate) fn find_mismatches(
+    fields: &[FieldMeta],
+    remote_fields: &[RemoteField],
+    user_field_names: bool,
+) -> Vec<SchemaMismatch> {
+    let mut mismatches = Vec::new();
+    for field in fields {
+        let Some(remote_field) = remote_fields
+            .iter()
+            .find(|remote_field| remote_field.id == field.id.id())
+        else {
+            mismatches.push(SchemaMismatch::DeletedField {
+                name: field.name,
+                id: field.id,
+            });
+            continue;
+        };
+
+        if user_field_names && remote_field.name != field.name {
+            mismatches.push(SchemaMismatch::RenamedField {
+                name: field.name,
+                id: field.id,
+                actual: remote_field.name.clone(),
+            });
+        }
+
+        if remote_field.field_type != field.field_type {
+            mismatches.push(SchemaMismatch::ChangedType {
+                name: field.name,
+                id: field.id,
+                expected: field.field_type,
+                actual: remote_field.field_type.clone(),
+            });
+            continue;
+        }
+
+        // Settings like the number of decimal places change the generated type just like the
+        // type of the field does
+        for (setting, expected, actual) in remote_field.settings(field) {
+            if let Some(expected) = expected
+                && actual.as_ref() != Some(&expected)
+            {
+                mismatches.push(SchemaMismatch::ChangedSetting {
+                    name: field.name,
+                    id: field.id,
+                    setting,
+                    expected,
+                    actual: actual.unwrap_or_else(|| "null".to_string()),
+                });
+            }
+        }
+
+        let new_options = remote_field
+            .select_options
+            .iter()
+            .flatten()
+            .filter(|option| !field.options.contains(&option.value.as_str()))
+            .map(|option| option.value.clone())
+            .collect::<Vec<String>>();
+        if !new_options.is_empty() {
+            mismatches.push(SchemaMismatch::NewSelectOptions {
+                name: field.name,
+                id: field.id,
+                options: new_options,
+            });
+        }
+    }
+    mismatches
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::schema::{FieldId, FieldMeta, RemoteField, SchemaMismatch, find_mismatches};
+
+    #[test]
+    fn test_field_id() {
+        const DEADLINE: FieldId = FieldId::new(1010);
+        assert_eq!(DEADLINE.id(), 1010);
+        assert_eq!(DEADLINE.to_string(), "field_1010");
+    }
+
+    const NO_SETTINGS: FieldMeta = FieldMeta {
+        name: "",
+        id: FieldId::new(0),
+        field_type: "",
+        read_only: false,
+        primary: false,
+        options: &[],
+        formula_type: None,
+        array_formula_type: None,
+        number_decimal_places: None,
+        number_negative: None,
+        date_include_time: None,
+        max_value: None,
+    };
+
+    #[test]
+    fn test_find_mismatches() {
+        const FIELDS: &[FieldMeta] = &[
+            FieldMeta {
+                name: "Name",
+                id: FieldId::new(1001),
+                field_type: "text",
+                read_only: false,
+                primary: true,
+                options: &[],
+                ..NO_SETTINGS
+            },
+            FieldMeta {
+                name: "Status",
+                id: FieldId::new(1002),
+                field_type: "single_select",
+                read_only: false,
+                primary: false,
+                options: &["Planned", "Done"],
+                ..NO_SETTINGS
+            },
+            FieldMeta {
+                name: "Budget",
+                id: FieldId::new(1003),
+                field_type: "number",
+                read_only: false,
+                primary: false,
+                options: &[],
+                ..NO_SETTINGS
+            },
+            FieldMeta {
+                name: "Notes",
+                id: FieldId::new(1004),
+                field_type: "long_text",
+                read_only: false,
+                primary: false,
+                options: &[],
+                ..NO_SETTINGS
+            },
+        ];
+        let remote_fields = serde_json::from_str::<Vec<RemoteField>>(
+            r#"[
+                {"id": 1001, "name": "Name", "type": "text", "primary": true},
+                {"id": 1002, "name": "Status", "type": "single_select", "select_options": [
+                    {"id": 1, "value": "Planned", "color": "blue"},
+                    {"id": 3, "value": "Blocked", "color": "red"}
+                ]},
+                {"id": 1003, "name": "Budget", "type": "text"},
+                {"id": 1005, "name": "Added later", "type": "formula", "select_options": null}
+            ]"#,
+        )
+        .unwrap();
+
+        let mismatches = find_mismatches(FIELDS, &remote_fields, false);
+        assert_eq!(
+            mismatches,
+            vec![
+                SchemaMismatch::NewSelectOptions {
+                    name: "Status",
+                    id: FieldId::new(1002),
+                    options: vec!["Blocked".to_string()],
+                },
+                SchemaMismatch::ChangedType {
+                    name: "Budget",
+                    id: FieldId::new(1003),
+                    expected: "number",
+                    actual: "text".to_string(),
+                },
+                SchemaMismatch::DeletedField {
+                    name: "Notes",
+                    id: FieldId::new(1004),
+                },
+            ]
+        );
+        assert_eq!(
+            mismatches[1].to_string(),
+            "field `Budget` (field_1003) changed its type from `number` to `text`"
+        );
+    }
+
+    #[test]
+    fn test_find_changed_settings() {
+        const FIELDS: &[FieldMeta] = &[
+            FieldMeta {
+                name: "Budget",
+                id: FieldId::new(1003),
+                field_type: "number",
+                number_decimal_places: Some(2),
+                number_negative: Some(false),
+                ..NO_SETTINGS
+            },
+            FieldMeta {
+                name: "Deadline",
+                id: FieldId::new(1010),
+                field_type: "date",
+                date_include_time: Some(false),
+                ..NO_SETTINGS
+            },
+        ];
+        let remote_fields = serde_json::from_str::<Vec<RemoteField>>(
+            r#"[
+                {"id": 1003, "name": "Budget", "type": "number", "number_decimal_places": 0,
+                    "number_negative": false},
+                {"id": 1010, "name": "Deadline", "type": "date", "date_include_time": false}
+            ]"#,
+        )
+        .unwrap();
+
+        let mismatches = find_mismatches(FIELDS, &remote_fields, false);
+        assert_eq!(
+            mismatches,
+            vec![SchemaMismatch::ChangedSetting {
+                name: "Budget",
+                id: FieldId::new(1003),
+                setting: "number_decimal_places",
+                expected: "2".to_string(),
+                actual: "0".to_string(),
+            }]
+        );
+        assert_eq!(
+            mismatches[0].to_string(),
+            "field `Budget` (field_1003) changed `number_decimal_places` from `2` to `0`"
+        );
+    }
+
+    #[test]
+    fn test_find_renamed_fields() {
+        const FIELDS: &[FieldMeta] = &[
+            FieldMeta {
+                name: "Name",
+                id: FieldId::new(1001),
+                field_type: "text",
+                primary: true,
+                ..NO_SETTINGS
+            },
+            FieldMeta {
+                name: "Budget",
+                id: FieldId::new(1003),
+                field_type: "number",
+                ..NO_SETTINGS
+            },
+        ];
+        let remote_fields = serde_json::from_str::<Vec<RemoteField>>(
+            r#"[
+                {"id": 1001, "name": "Name", "type": "text", "primary": true},
+                {"id": 1003, "name": "Costs", "type": "number"}
+            ]"#,
+        )
+        .unwrap();
+
+        assert!(find_mismatches(FIELDS, &remote_fields, false).is_empty());
+
+        let mismatches = find_mismatches(FIELDS, &remote_fields, true);
+        assert_eq!(
+            mismatches,
+            vec![SchemaMismatch::RenamedField {
+                name: "Budget",
+                id: FieldId::new(1003),
+                actual: "Costs".to_string(),
+            }]
+        );
+        assert_eq!(
+            mismatches[0].to_string(),
+            "field `Budget` (field_1003) was renamed to `Costs`"
+        );
+    }
+}
@@ -0,0 +1,286 @@
+use crate::client::{BaserowObject, BaserowWriteObject, Client, ConflictSnafu, Error};
+use serde::de::DeserializeOwned;
+use snafu::ensure;
+use std::ops::{Deref, DerefMut};
+
+/// Implemented by the generated read models, allows computing which fields of a row changed.
+pub trait Trackable: BaserowObject + DeserializeOwned + Clone {
+    type Patch: BaserowWriteObject<Read = Self>;
+
+    /// Build a patch that contains all writable fields whose value differs from `original`.
+    fn diff(&self, original: &Self) -> Self::Patch;
+
+    /// Names of the writable fields whose value differs between `self` and `other`.
+    fn changed_fields(&self, other: &Self) -> Vec<&'static str>;
+
+    /// Value of the last modified field of the table, if it has one that includes the time.
+    fn updated_on(&self) -> Option<String>;
+}
+
+/// A row that remembers the values it was read with, so that saving it only sends the fields
+/// that were changed since.
+///
+/// `Tracked` dereferences to the row, so fields can be changed directly:
+///
+/// ```ignore
+/// let mut project = client.get_tracked::<Projects>(42).await?;
+/// project.notes = Some("Kickoff moved".to_string());
+/// project.save_checked(&client).await?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct Tracked<T> {
+    row_id: usize,
+    original: T,
+    current: T,
+}
+
+impl<T> Tracked<T>
+where
+    T: Trackable,
+{
+    pub fn new(row_id: usize, row: T) -> Self {
+        Self {
+            row_id,
+            original: row.clone(),
+            current: row,
+        }
+    }
+
+    /// The internal Baserow id of the row.
+    pub fn row_id(&self) -> usize {
+        self.row_id
+    }
+
+    /// The row as it was read from Baserow, or last saved.
+    pub fn original(&self) -> &T {
+        &self.original
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        !self.current.changed_fields(&self.original).is_empty()
+    }
+
+    /// Patch containing only the fields that were changed since the row was read.
+    pub fn patch(&self) -> T::Patch {
+        self.current.diff(&self.original)
+    }
+
+    pub fn into_inner(self) -> T {
+        self.current
+    }
+
+    /// Send the fields that were changed since the row was read to Baserow.
+    ///
+    /// Changes that other users made to the same fields in the meantime are overwritten, use
+    /// [`Tracked::save_checked`] to detect these.
+    pub async fn save(&mut self, client: &Client) -> Result<(), Error> {
+        if self.is_dirty() {
+            client.update_row(self.row_id, &self.patch()).await?;
+            self.original = self.current.clone();
+        }
+        Ok(())
+    }
+
+    /// Like [`Tracked::save`], but reads the row again before writing and fails with
+    /// [`Error::Conflict`] if someone else changed any of the fields we are about to write.
+    pub async fn save_checked(&mut self, client: &Client) -> Result<(), Error> {
+        if !self.is_dirty() {
+            return Ok(());
+        }
+
+        let remote = client.get::<T>(self.row_id).await?;
+        check_conflicts(self.row_id, &self.original, &self.current, &remote)?;
+        self.save(client).await
+    }
+}
+
+// Fails if a field changed both locally and in `remote` since `original` was read
+fn check_conflicts<T>(row_id: usize, original: &T, current: &T, remote: &T) -> Result<(), Error>
+where
+    T: Trackable,
+{
+    // If the table has a last modified field with the time and it didn't move, nobody touched
+    // the row
+    let unchanged = original.updated_on().is_some() && remote.updated_on() == original.updated_on();
+    if unchanged {
+        return Ok(());
+    }
+
+    let changed_remotely = remote.changed_fields(original);
+    let conflicts = current
+        .changed_fields(original)
+        .iter()
+        .filter(|field| changed_remotely.contains(field))
+        .map(|field| field.to_string())
+        .collect::<Vec<String>>();
+    ensure!(
+        conflicts.is_empty(),
+        ConflictSnafu {
+            row_id,
+            fields: conflicts
+        }
+    );
+    Ok(())
+}
+
+impl<T> Deref for Tracked<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.current
+    }
+}
+
+impl<T> DerefMut for Tracked<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.current
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::{BaserowObject, BaserowWriteObject, Error, Identifier};
+    use crate::tracked::{Trackable, Tracked, check_conflicts};
+    use crate::validation::{Validate, Violation};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+    struct Task {
+        name: Option<String>,
+        done: Option<bool>,
+        updated_on: Option<String>,
+    }
+
+    #[derive(Serialize, Debug, Default)]
+    struct TaskPatch {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        name: Option<Option<String>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        done: Option<Option<bool>>,
+    }
+
+    impl BaserowObject for Task {
+        fn get_static_table_id() -> usize {
+            1
+        }
+
+        fn get_table_id(&self) -> usize {
+            Self::get_static_table_id()
+        }
+
+        fn get_id(&self) -> Identifier {
+            Identifier::Text {
+                id: self.name.clone(),
+            }
+        }
+
+        fn get_table_id_field(&self) -> String {
+            "field_1".to_string()
+        }
+    }
+
+    impl BaserowWriteObject for TaskPatch {
+        type Read = Task;
+    }
+
+    impl Validate for TaskPatch {
+        fn validate(&self) -> Vec<Violation> {
+            Vec::new()
+        }
+    }
+
+    impl Trackable for Task {
+        type Patch = TaskPatch;
+
+        fn diff(&self, original: &Self) -> TaskPatch {
+            let mut patch = TaskPatch::default();
+            if self.name != original.name {
+                patch.name = Some(self.name.clone());
+            }
+            if self.done != original.done {
+                patch.done = Some(self.done);
+            }
+            patch
+        }
+
+        fn changed_fields(&self, other: &Self) -> Vec<&'static str> {
+            let mut changed = Vec::new();
+            if self.name != other.name {
+                changed.push("name");
+            }
+            if self.done != other.done {
+                changed.push("done");
+            }
+            changed
+        }
+
+        fn updated_on(&self) -> Option<String> {
+            self.updated_on.clone()
+        }
+    }
+
+    #[test]
+    fn test_patch_only_contains_changes() {
+        let mut task = Tracked::new(
+            7,
+            Task {
+                name: Some("Write docs".to_string()),
+                done: None,
+                updated_on: None,
+            },
+        );
+        assert!(!task.is_dirty());
+
+        task.done = Some(true);
+        assert!(task.is_dirty());
+        assert_eq!(
+            serde_json::to_string(&task.patch()).unwrap(),
+            r#"{"done":true}"#
+        );
+    }
+
+    fn task(name: &str, done: bool, updated_on: Option<&str>) -> Task {
+        Task {
+            name: Some(name.to_string()),
+            done: Some(done),
+            updated_on: updated_on.map(|updated_on| updated_on.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_no_conflict_when_other_field_changed() {
+        let original = task("Write docs", false, Some("2025-01-01T10:00:00Z"));
+        let current = task("Write docs", true, Some("2025-01-01T10:00:00Z"));
+        let remote = task("Write the docs", false, Some("2025-01-01T11:00:00Z"));
+        assert!(check_conflicts(7, &original, &current, &remote).is_ok());
+    }
+
+    #[test]
+    fn test_conflict_when_same_field_changed() {
+        let original = task("Write docs", false, Some("2025-01-01T10:00:00Z"));
+        let current = task("Write docs", true, Some("2025-01-01T10:00:00Z"));
+        let remote = task("Write the docs", true, Some("2025-01-01T11:00:00Z"));
+        match check_conflicts(7, &original, &current, &remote) {
+            Err(Error::Conflict { row_id, fields }) => {
+                assert_eq!(row_id, 7);
+                assert_eq!(fields, vec!["done".to_string()]);
+            }
+            result => panic!("expected a conflict, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_conflicts_without_last_modified_field() {
+        // Without a last modified field the fields themselves are compared
+        let original = task("Write docs", false, None);
+        let current = task("Write docs", true, None);
+        let remote = task("Write the docs", false, None);
+        assert!(check_conflicts(7, &original, &current, &remote).is_ok());
+
+        let remote = task("Write docs", true, None);
+        assert!(matches!(
+            check_conflicts(7, &original, &current, &remote),
+            Err(Error::Conflict { .. })
+        ));
+    }
+}
@@ -20,6 +20,9 @@ impl UrlBuilder {
     const CLOUD_URL: &'static str = "https://api.baserow.io/";
     // API stubs to build needed endpoints from for requests
     const RECORD_URL: &'static str = "/api/database/rows/table/";
+    const FIELDS_URL: &'static str = "/api/database/fields/table/";
+    const UPLOAD_FILE_URL: &'static str = "/api/user-files/upload-file/";
+    const UPLOAD_FILE_VIA_URL_URL: &'static str = "/api/user-files/upload-via-url/";
 
     pub fn new(base_url: Option<&str>) -> Result<Self, Error> {
         match base_url {
@@ -34,54 +37,146 @@ impl UrlBuilder {
         self.base_url.join(Self::RECORD_URL).unwrap()
     }
 
-    pub fn get_list_records_url(&self, table_id: usize) -> Result<Url, Error> {
-        self
-            .get_record_url()
-            .join(&format!("{}/", &table_id.to_string()))
+    pub fn get_table_fields_url(&self, table_id: usize) -> Result<Url, Error> {
+        self.base_url
+            .join(&format!("{}{}/", Self::FIELDS_URL, table_id))
             .context(BuildUrlSnafu {
-                action: "listing records",
+                action: "listing fields",
             })
     }
 
-    pub fn get_create_record_url(&self, table_id: usize) -> Result<Url, Error> {
-        self
-            .get_record_url()
-            .join(&format!("{}/",table_id.to_string()))
+    pub fn get_upload_file_url(&self) -> Result<Url, Error> {
+        self.base_url
+            .join(Self::UPLOAD_FILE_URL)
             .context(BuildUrlSnafu {
-                action: "creating record",
+                action: "uploading file",
             })
     }
 
+    pub fn get_upload_file_via_url_url(&self) -> Result<Url, Error> {
+        self.base_url
+            .join(Self::UPLOAD_FILE_VIA_URL_URL)
+            .context(BuildUrlSnafu {
+                action: "uploading file via url",
+            })
+    }
+
+    // The record urls take whether Baserow should refer to fields by their names instead of
+    // `field_<id>` in rows and filters, which depends on the type the row is exchanged as.
+    pub fn get_list_records_url(
+        &self,
+        table_id: usize,
+        user_field_names: bool,
+    ) -> Result<Url, Error> {
+        self.get_table_records_url(table_id, "listing records")
+            .map(|url| with_field_names(url, user_field_names))
+    }
+
+    pub fn get_create_record_url(
+        &self,
+        table_id: usize,
+        user_field_names: bool,
+    ) -> Result<Url, Error> {
+        self.get_table_records_url(table_id, "creating record")
+            .map(|url| with_field_names(url, user_field_names))
+    }
+
     pub fn get_find_record_url(
         &self,
         table_id: usize,
         field_id: &str,
         id: &str,
+        user_field_names: bool,
     ) -> Result<Url, Error> {
-        self
-            .get_create_record_url(table_id)?
-            .join(&format!("?filter__{}__equal={}", field_id, id))
-            .context(BuildUrlSnafu {
-                action: "finding record by id",
-            })
+        let mut url = self.get_table_records_url(table_id, "finding record by id")?;
+        url.query_pairs_mut()
+            .append_pair(&format!("filter__{}__equal", field_id), id);
+        Ok(with_field_names(url, user_field_names))
     }
 
-    pub fn get_update_record_url(&self, table_id: usize, record_id: usize) -> Result<Url, Error> {
-        self
-            .get_create_record_url(table_id)?
-            .join(&format!("{}/", record_id.to_string()))
-            .context(BuildUrlSnafu {
-                action: "updating record by id",
-            })
+    pub fn get_read_record_url(
+        &self,
+        table_id: usize,
+        record_id: usize,
+        user_field_names: bool,
+    ) -> Result<Url, Error> {
+        self.get_record_by_id_url(table_id, record_id, "reading record by id")
+            .map(|url| with_field_names(url, user_field_names))
+    }
+
+    pub fn get_update_record_url(
+        &self,
+        table_id: usize,
+        record_id: usize,
+        user_field_names: bool,
+    ) -> Result<Url, Error> {
+        self.get_record_by_id_url(table_id, record_id, "updating record by id")
+            .map(|url| with_field_names(url, user_field_names))
+    }
+
+    fn get_table_records_url(&self, table_id: usize, action: &str) -> Result<Url, Error> {
+        self.get_record_url()
+            .join(&format!("{}/", table_id))
+            .context(BuildUrlSnafu { action })
+    }
+
+    fn get_record_by_id_url(
+        &self,
+        table_id: usize,
+        record_id: usize,
+        action: &str,
+    ) -> Result<Url, Error> {
+        self.get_table_records_url(table_id, action)?
+            .join(&format!("{}/", record_id))
+            .context(BuildUrlSnafu { action })
+    }
+}
+
+fn with_field_names(mut url: Url, user_field_names: bool) -> Url {
+    if user_field_names {
+        url.query_pairs_mut()
+            .append_pair("user_field_names", "true");
     }
+    url
 }
 
 impl Default for UrlBuilder {
     fn default() -> Self {
         Self {
-            // This unwrap is okay, if we ever hit that it is an error in the code, 
+            // This unwrap is okay, if we ever hit that it is an error in the code,
             // as the parsed url is hard-coded
             base_url: Url::from_str(Self::CLOUD_URL).unwrap(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::url_builder::UrlBuilder;
+
+    #[test]
+    fn test_user_field_names() {
+        let url_builder = UrlBuilder::default();
+        assert_eq!(
+            url_builder
+                .get_read_record_url(101, 7, false)
+                .unwrap()
+                .as_str(),
+            "https://api.baserow.io/api/database/rows/table/101/7/"
+        );
+        assert_eq!(
+            url_builder
+                .get_list_records_url(101, true)
+                .unwrap()
+                .as_str(),
+            "https://api.baserow.io/api/database/rows/table/101/?user_field_names=true"
+        );
+        assert_eq!(
+            url_builder
+                .get_find_record_url(101, "Project name", "Apollo & Co", true)
+                .unwrap()
+                .as_str(),
+            "https://api.baserow.io/api/database/rows/table/101/?filter__Project+name__equal=Apollo+%26+Co&user_field_names=true"
+        );
+    }
+}
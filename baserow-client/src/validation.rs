@@ -0,0 +1,105 @@
+use std::fmt;
+use url::Url;
+
+// Baserow limits phone numbers to these characters and length
+const PHONE_NUMBER_CHARACTERS: &str = "0123456789NnXx,+._*()#=;/ -";
+const MAX_PHONE_NUMBER_LENGTH: usize = 100;
+
+/// A value that Baserow would refuse, found before sending it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    pub field: String,
+    pub message: String,
+}
+
+impl Violation {
+    pub fn new(field: &str, message: &str) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// Implemented by the generated structs, checks field values against the configuration of the
+/// fields in Baserow.
+pub trait Validate {
+    /// All violations found in this object, empty if it is valid.
+    fn validate(&self) -> Vec<Violation>;
+}
+
+pub fn is_valid_email(value: &str) -> bool {
+    match value.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !domain.contains('@')
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+                && !value.contains(char::is_whitespace)
+        }
+        None => false,
+    }
+}
+
+/// Baserow also accepts urls without a scheme, like `www.example.com`.
+pub fn is_valid_url(value: &str) -> bool {
+    if value.contains(char::is_whitespace) {
+        return false;
+    }
+    match Url::parse(value) {
+        Ok(url) => url.has_host(),
+        Err(_) => Url::parse(&format!("https://{}", value))
+            .ok()
+            .and_then(|url| url.host_str().map(|host| host.contains('.')))
+            .unwrap_or(false),
+    }
+}
+
+pub fn is_valid_phone_number(value: &str) -> bool {
+    !value.is_empty()
+        && value.chars().count() <= MAX_PHONE_NUMBER_LENGTH
+        && value.chars().all(|c| PHONE_NUMBER_CHARACTERS.contains(c))
+}
+
+pub fn exceeds_decimal_places(value: f64, decimal_places: u32) -> bool {
+    let scaled = value * 10f64.powi(decimal_places as i32);
+    // Allow for the imprecision of floats, 0.1 + 0.2 should still count as having one place.
+    // Floats get coarser as they grow, so the tolerance has to grow with them
+    let tolerance = (scaled.abs() * f64::EPSILON * 4.0).max(1e-6);
+    (scaled - scaled.round()).abs() > tolerance
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::validation::{
+        exceeds_decimal_places, is_valid_email, is_valid_phone_number, is_valid_url,
+    };
+
+    #[test]
+    fn test_validators() {
+        assert!(is_valid_email("jane.doe@example.com"));
+        assert!(!is_valid_email("jane.doe@localhost"));
+        assert!(!is_valid_email("jane doe@example.com"));
+
+        assert!(is_valid_url("https://baserow.io/docs"));
+        assert!(is_valid_url("www.example.com"));
+        assert!(!is_valid_url("not a url"));
+
+        assert!(is_valid_phone_number("+49 (170) 123-4567"));
+        assert!(!is_valid_phone_number("call me"));
+
+        assert!(!exceeds_decimal_places(0.1 + 0.2, 1));
+        assert!(exceeds_decimal_places(12.345, 2));
+        assert!(!exceeds_decimal_places(1234567890.12, 2));
+        assert!(!exceeds_decimal_places(2500000000.05, 2));
+        assert!(!exceeds_decimal_places(85817477812.6, 2));
+        assert!(exceeds_decimal_places(2500000000.055, 2));
+    }
+}
@@ -0,0 +1,85 @@
+use baserow_client::client::{BaserowObject, BaserowWriteObject, Identifier};
+use baserow_client::schema::FieldId;
+use baserow_client::validation::{Validate, Violation};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, BaserowObject)]
+#[baserow(table = 101)]
+struct Project {
+    #[baserow(field = 1001, primary)]
+    #[serde(rename = "field_1001")]
+    name: Option<String>,
+    #[baserow(field = 1005)]
+    #[serde(rename = "field_1005")]
+    budget: Option<f64>,
+    #[serde(skip)]
+    #[allow(dead_code)]
+    local_note: String,
+}
+
+#[derive(BaserowObject)]
+#[baserow(table = 102, read_only)]
+struct Invoice {
+    #[baserow(primary, field = 2001)]
+    number: usize,
+}
+
+#[derive(Serialize, BaserowObject)]
+#[baserow(table = 103, custom_validation)]
+struct Customer {
+    #[baserow(field = 3001, primary)]
+    #[serde(rename = "field_3001")]
+    name: String,
+}
+
+impl Validate for Customer {
+    fn validate(&self) -> Vec<Violation> {
+        if self.name.is_empty() {
+            vec![Violation::new("name", "must not be empty")]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+// Stands in for `Client::create` and friends, which need the same bound
+fn write_table_id<T: BaserowWriteObject>(obj: &T) -> (usize, usize) {
+    (T::Read::get_static_table_id(), obj.validate().len())
+}
+
+#[test]
+fn test_derive_baserow_object() {
+    let project = serde_json::from_str::<Project>(
+        r#"{"id": 1, "field_1001": "Apollo", "field_1005": 1200.5}"#,
+    )
+    .unwrap();
+
+    assert_eq!(Project::get_static_table_id(), 101);
+    assert_eq!(project.get_table_id(), Project::TABLE_ID);
+    assert_eq!(project.get_table_id_field(), "field_1001");
+    assert_eq!(project.get_id().get_string().as_deref(), Some("Apollo"));
+    assert_eq!(Project::FIELD_BUDGET, FieldId::new(1005));
+    assert_eq!(project.budget, Some(1200.5));
+
+    let invoice = Invoice { number: 7 };
+    assert!(matches!(
+        invoice.get_id(),
+        Identifier::UnsignedNumber { id: Some(7) }
+    ));
+    assert_eq!(invoice.get_table_id_field(), "field_2001");
+}
+
+#[test]
+fn test_derive_write_object() {
+    let project = Project {
+        name: Some("Apollo".to_string()),
+        budget: None,
+        local_note: String::new(),
+    };
+    assert_eq!(write_table_id(&project), (101, 0));
+
+    let customer = Customer {
+        name: String::new(),
+    };
+    assert_eq!(write_table_id(&customer), (103, 1));
+}
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 use snafu::{ResultExt, Snafu};
+use std::path::Path;
+use wildmatch::WildMatch;
 
 #[derive(Snafu, Debug)]
 pub enum Error {
@@ -12,23 +14,156 @@ pub enum Error {
     ParseConfigFile { source: serde_json::Error },
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub  struct Database {
+/// A database to generate code for.
+///
+/// Tables and fields can be filtered with patterns that support `*` and `?` as wildcards.
+/// Field patterns match the name of the field, or the name of the table and the field if they
+/// contain a slash, like `Projects/Internal*`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Database {
     pub name: String,
     pub id: usize,
+    /// Only tables matching one of these patterns are generated, all tables if empty
+    #[serde(default)]
+    pub include_tables: Vec<String>,
+    #[serde(default)]
+    pub exclude_tables: Vec<String>,
+    /// Only fields matching one of these patterns are generated, all fields if empty
+    #[serde(default)]
+    pub include_fields: Vec<String>,
+    #[serde(default)]
+    pub exclude_fields: Vec<String>,
+}
+
+impl Database {
+    pub fn includes_table(&self, table_name: &str) -> bool {
+        (self.include_tables.is_empty()
+            || self
+                .include_tables
+                .iter()
+                .any(|pattern| matches_pattern(pattern, table_name)))
+            && !self
+                .exclude_tables
+                .iter()
+                .any(|pattern| matches_pattern(pattern, table_name))
+    }
+
+    pub fn includes_field(&self, table_name: &str, field_name: &str) -> bool {
+        (self.include_fields.is_empty()
+            || self
+                .include_fields
+                .iter()
+                .any(|pattern| matches_field_pattern(pattern, table_name, field_name)))
+            && !self
+                .exclude_fields
+                .iter()
+                .any(|pattern| matches_field_pattern(pattern, table_name, field_name))
+    }
+}
+
+fn matches_pattern(pattern: &str, name: &str) -> bool {
+    WildMatch::new(pattern).matches(name)
+}
+
+fn matches_field_pattern(pattern: &str, table_name: &str, field_name: &str) -> bool {
+    match pattern.split_once('/') {
+        Some((table_pattern, field_pattern)) => {
+            matches_pattern(table_pattern, table_name) && matches_pattern(field_pattern, field_name)
+        }
+        None => matches_pattern(pattern, field_name),
+    }
+}
+
+/// Reference to a table or field, either by its id or by its name in Baserow.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum Reference {
+    Id(usize),
+    Name(String),
+}
+
+impl Reference {
+    pub fn matches(&self, id: usize, name: &str) -> bool {
+        match self {
+            Reference::Id(reference_id) => reference_id.eq(&id),
+            Reference::Name(reference_name) => reference_name.eq(name),
+        }
+    }
+}
+
+/// Changes to the generated code for a single table or field, for cases where the generator
+/// can't know better, like text fields that actually contain JSON.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Override {
+    pub table: Reference,
+    /// Without a field the override applies to the struct of the table, only `rename` is
+    /// supported then
+    pub field: Option<Reference>,
+    pub rust_type: Option<String>,
+    /// Module to use for serializing and deserializing the field, like `#[serde(with = "...")]`
+    pub with: Option<String>,
+    pub rename: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct BaserowConfig {
+    /// Can be left out if the token is passed in an environment variable
+    #[serde(default)]
     pub token: String,
     pub databases: Vec<Database>,
     pub target_directory: String,
+    /// Url of a self hosted Baserow instance, Baserow cloud if not set
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// Generate `rust_decimal::Decimal` for number fields with decimal places
+    #[serde(default)]
+    pub exact_decimals: bool,
+    /// Generate dedicated types for email, url, phone number and uuid fields
+    #[serde(default)]
+    pub typed_strings: bool,
+    /// Refer to fields by their names instead of their ids, renaming a field in Baserow then
+    /// breaks the generated code
+    #[serde(default)]
+    pub user_field_names: bool,
+    /// Write every table into a file of its own instead of one file per database
+    #[serde(default)]
+    pub file_per_table: bool,
+    #[serde(default)]
+    pub overrides: Vec<Override>,
 }
 
 impl BaserowConfig {
-    pub fn new() -> Result<Self, Error> {
-        let path = "./baserow_config.json";
-        serde_json::from_str(&std::fs::read_to_string(path).context(ReadConfigFileSnafu { path })?)
-            .context(ParseConfigFileSnafu)
+    pub fn from_file(path: &Path) -> Result<Self, Error> {
+        let path = path.display().to_string();
+        serde_json::from_str(
+            &std::fs::read_to_string(&path).context(ReadConfigFileSnafu { path: &path })?,
+        )
+        .context(ParseConfigFileSnafu)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::baserow_config::Database;
+
+    #[test]
+    fn test_filters() {
+        let database = serde_json::from_str::<Database>(
+            r#"{
+                "name": "Test DB",
+                "id": 10,
+                "exclude_tables": ["Scratch*"],
+                "exclude_fields": ["Internal ?", "Projects/Notes"]
+            }"#,
+        )
+        .unwrap();
+
+        assert!(database.includes_table("Projects"));
+        assert!(!database.includes_table("Scratch pad"));
+
+        assert!(database.includes_field("Projects", "Name"));
+        assert!(!database.includes_field("Projects", "Internal 1"));
+        assert!(!database.includes_field("Projects", "Notes"));
+        assert!(database.includes_field("Customers", "Notes"));
     }
 }
@@ -1,8 +1,11 @@
+use crate::identifier::{Identifiers, to_identifier};
 use convert_case::Case::Pascal;
 use convert_case::{Case, Casing};
+use proc_macro2::Ident;
 use quote::__private::TokenStream;
 use quote::{format_ident, quote};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SharedFields {
@@ -17,6 +20,41 @@ pub struct SharedFields {
     pub description: Option<String>,
     pub database_id: usize,
     pub workspace_id: usize,
+    // Not part of the api response, the generator fills this in from its configuration
+    #[serde(skip)]
+    pub options: FieldOptions,
+}
+
+/// Settings from the codegen configuration that change how a field is generated.
+#[derive(Debug, Clone, Default)]
+pub struct FieldOptions {
+    /// Map numbers with decimal places to `rust_decimal::Decimal` instead of `f64`
+    pub exact_decimals: bool,
+    /// Map email, url, phone number and uuid fields to dedicated types instead of `String`
+    pub typed_strings: bool,
+    /// Rust type to use instead of the one derived from the field type
+    pub rust_type: Option<String>,
+    /// Module to pass to `#[serde(with = "...")]` for this field
+    pub serde_with: Option<String>,
+    /// Name of the struct field to use instead of the one derived from the field name
+    pub rename: Option<String>,
+    /// Name of the type generated for the field, instead of the one derived from the table and
+    /// field names
+    pub type_name: Option<String>,
+    /// Refer to the field by its name in Baserow instead of `field_<id>` when (de)serializing
+    pub user_field_names: bool,
+}
+
+/// Settings of a field in Baserow that the type generated for it depends on, named like in the
+/// api. Settings the field doesn't have are `None`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TypeSettings {
+    pub formula_type: Option<String>,
+    pub array_formula_type: Option<String>,
+    pub number_decimal_places: Option<usize>,
+    pub number_negative: Option<bool>,
+    pub date_include_time: Option<bool>,
+    pub max_value: Option<usize>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -205,6 +243,11 @@ pub enum TableField {
     Lookup {
         #[serde(flatten)]
         shared_fields: SharedFields,
+        through_field_id: Option<isize>,
+        target_field_id: Option<isize>,
+        // Not part of the api response, the generator fills this in once all tables are known
+        #[serde(skip)]
+        target: Option<LookupTarget>,
     },
     #[serde(rename = "multiple_collaborators")]
     MultipleCollaborators {
@@ -235,10 +278,17 @@ pub enum TableField {
     },
 }
 
+/// The field a lookup field retrieves its values from, along with the name of its table.
+#[derive(Debug, Clone)]
+pub struct LookupTarget {
+    pub table_name: String,
+    pub field: Box<TableField>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Collaborator {
-    id: isize,
-    name: String,
+    pub id: isize,
+    pub name: String,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -248,31 +298,155 @@ pub struct SelectOption {
     pub color: String,
 }
 
-pub fn cleanup_name(dirty_name: &str) -> String {
-    let first_stage = dirty_name.replace(
-        &['(', ')', ',', '\"', '.', ';', ':', '\'', '/', '@'][..],
-        "",
-    );
-    // Check if starts with number, that is not a legal rust identifier
-    if first_stage[0..1].parse::<u8>().is_ok() {
-        format!("a{}", first_stage)
+// Baserow always exchanges dates in ISO format and timestamps in UTC over the api,
+// `date_format` and `date_force_timezone` only affect how the values are displayed in the
+// web frontend, so whether the field includes a time is all we need to pick a type.
+fn date_rust_type(include_time: bool) -> String {
+    if include_time {
+        "DateTime<Utc>".to_string()
+    } else {
+        "NaiveDate".to_string()
+    }
+}
+
+fn date_deserializer(include_time: bool) -> TokenStream {
+    if include_time {
+        quote! {, deserialize_with = "datetime_or_null"}
+    } else {
+        quote! {, deserialize_with = "date_or_null"}
+    }
+}
+
+fn number_rust_type(decimal_places: isize, negative: bool, exact: bool) -> String {
+    if decimal_places.gt(&0) && exact {
+        "Decimal".to_string()
+    } else if decimal_places.gt(&0) {
+        "f64".to_string()
+    } else if negative {
+        "isize".to_string()
+    } else {
+        "usize".to_string()
+    }
+}
+
+fn number_deserializer(decimal_places: isize, negative: bool, exact: bool) -> TokenStream {
+    if decimal_places.gt(&0) && exact {
+        // Baserow expects exactly the configured number of decimal places
+        let serializer = format!("decimal_as_string::<{}, _>", decimal_places);
+        quote! {, deserialize_with = "decimal_or_null", serialize_with = #serializer}
+    } else if decimal_places.gt(&0) {
+        quote! {, deserialize_with = "float_or_null"}
+    } else if negative {
+        quote! {, deserialize_with = "isize_or_null"}
     } else {
-        first_stage
+        quote! {, deserialize_with = "usize_or_null"}
+    }
+}
+
+// Formula and rollup fields don't carry a sign setting, so numbers resulting from them are
+// always treated as possibly negative.
+// Single select formulas need a generated enum, which the caller has to take care of.
+fn formula_rust_type(
+    formula_type: &str,
+    array_formula_type: Option<&str>,
+    number_decimal_places: Option<isize>,
+    date_include_time: Option<bool>,
+) -> String {
+    match formula_type {
+        "number" => number_rust_type(number_decimal_places.unwrap_or(0), true, false),
+        "boolean" => "bool".to_string(),
+        "date" => date_rust_type(date_include_time.unwrap_or(false)),
+        "duration" => "TimeDelta".to_string(),
+        "array" => {
+            let value_type = match array_formula_type {
+                // Arrays inside of arrays and selects without options to generate an enum from
+                // are left for the user to interpret
+                None | Some("array") | Some("single_select") => "serde_json::Value".to_string(),
+                // Durations are sent as seconds, which TimeDelta has no serde representation
+                // for
+                Some("duration") => "f64".to_string(),
+                Some(array_formula_type) => formula_rust_type(
+                    array_formula_type,
+                    None,
+                    number_decimal_places,
+                    date_include_time,
+                ),
+            };
+            format!("Vec<LookupValue<{}>>", value_type)
+        }
+        "text" | "char" | "date_interval" | "url" | "email" => "String".to_string(),
+        // Links, buttons, files, multiple selects and types added to Baserow later are sent as
+        // objects or arrays, which are left for the user to interpret
+        _ => "serde_json::Value".to_string(),
+    }
+}
+
+fn formula_deserializer(
+    formula_type: &str,
+    number_decimal_places: Option<isize>,
+    date_include_time: Option<bool>,
+) -> Option<TokenStream> {
+    match formula_type {
+        "number" => Some(number_deserializer(
+            number_decimal_places.unwrap_or(0),
+            true,
+            false,
+        )),
+        "date" => Some(date_deserializer(date_include_time.unwrap_or(false))),
+        "duration" => Some(quote! {
+            , deserialize_with = "duration_or_null", serialize_with = "duration_as_seconds"
+        }),
+        _ => None,
+    }
+}
+
+// Options are numbered in the order Baserow returns them if their names collide
+fn select_variant_names(select_options: &[SelectOption]) -> Vec<Ident> {
+    let mut identifiers = Identifiers::new(Pascal);
+    select_options
+        .iter()
+        .map(|option| {
+            format_ident!(
+                "{}",
+                identifiers.unique(to_identifier(&option.value, Pascal))
+            )
+        })
+        .collect()
+}
+
+fn select_enum(rust_name: &str, select_options: &[SelectOption]) -> TokenStream {
+    let mut variants = TokenStream::new();
+    for (option, rust_variant_name) in select_options
+        .iter()
+        .zip(select_variant_names(select_options))
+    {
+        let serialized_name = &option.value;
+        variants.extend(quote! {
+            #[serde(rename = #serialized_name)]
+            #[strum(serialize = #serialized_name)]
+            #rust_variant_name {color: String, id: usize},
+        });
+    }
+
+    let rust_name = format_ident!("{}", rust_name);
+    quote! {
+        #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, EnumString, Display)]
+        #[serde(tag = "value")]
+        pub enum #rust_name {
+            #variants
+        }
     }
 }
 
 impl TableField {
     fn clean_name(dirty_name: &str) -> String {
-        let clean_name = cleanup_name(dirty_name);
-        let clean_name = clean_name.to_case(Case::Snake);
-        if clean_name.eq("type") {
-            "ty".to_string()
-        } else {
-            clean_name
-        }
+        to_identifier(dirty_name, Case::Snake)
     }
 
     pub fn get_name(&self) -> String {
+        if let Some(rename) = &self.get_shared_fields().options.rename {
+            return rename.clone();
+        }
         match self {
             TableField::Text { shared_fields, .. } => Self::clean_name(&shared_fields.name),
             TableField::LongText { shared_fields, .. } => Self::clean_name(&shared_fields.name),
@@ -310,6 +484,70 @@ impl TableField {
         }
     }
 
+    fn get_shared_fields(&self) -> &SharedFields {
+        match self {
+            TableField::Text { shared_fields, .. } => shared_fields,
+            TableField::LongText { shared_fields, .. } => shared_fields,
+            TableField::Url { shared_fields, .. } => shared_fields,
+            TableField::Email { shared_fields, .. } => shared_fields,
+            TableField::Number { shared_fields, .. } => shared_fields,
+            TableField::Rating { shared_fields, .. } => shared_fields,
+            TableField::Boolean { shared_fields, .. } => shared_fields,
+            TableField::Date { shared_fields, .. } => shared_fields,
+            TableField::LastModified { shared_fields, .. } => shared_fields,
+            TableField::LastModifiedBy { shared_fields, .. } => shared_fields,
+            TableField::CreatedOn { shared_fields, .. } => shared_fields,
+            TableField::CreatedBy { shared_fields, .. } => shared_fields,
+            TableField::Duration { shared_fields, .. } => shared_fields,
+            TableField::LinkRow { shared_fields, .. } => shared_fields,
+            TableField::File { shared_fields, .. } => shared_fields,
+            TableField::SingleSelect { shared_fields, .. } => shared_fields,
+            TableField::MultipleSelect { shared_fields, .. } => shared_fields,
+            TableField::PhoneNumber { shared_fields, .. } => shared_fields,
+            TableField::Formula { shared_fields, .. } => shared_fields,
+            TableField::Count { shared_fields, .. } => shared_fields,
+            TableField::Rollup { shared_fields, .. } => shared_fields,
+            TableField::Lookup { shared_fields, .. } => shared_fields,
+            TableField::MultipleCollaborators { shared_fields, .. } => shared_fields,
+            TableField::Uuid { shared_fields, .. } => shared_fields,
+            TableField::AutoNumber { shared_fields, .. } => shared_fields,
+            TableField::Password { shared_fields, .. } => shared_fields,
+            TableField::Ai { shared_fields, .. } => shared_fields,
+        }
+    }
+
+    fn get_shared_fields_mut(&mut self) -> &mut SharedFields {
+        match self {
+            TableField::Text { shared_fields, .. } => shared_fields,
+            TableField::LongText { shared_fields, .. } => shared_fields,
+            TableField::Url { shared_fields, .. } => shared_fields,
+            TableField::Email { shared_fields, .. } => shared_fields,
+            TableField::Number { shared_fields, .. } => shared_fields,
+            TableField::Rating { shared_fields, .. } => shared_fields,
+            TableField::Boolean { shared_fields, .. } => shared_fields,
+            TableField::Date { shared_fields, .. } => shared_fields,
+            TableField::LastModified { shared_fields, .. } => shared_fields,
+            TableField::LastModifiedBy { shared_fields, .. } => shared_fields,
+            TableField::CreatedOn { shared_fields, .. } => shared_fields,
+            TableField::CreatedBy { shared_fields, .. } => shared_fields,
+            TableField::Duration { shared_fields, .. } => shared_fields,
+            TableField::LinkRow { shared_fields, .. } => shared_fields,
+            TableField::File { shared_fields, .. } => shared_fields,
+            TableField::SingleSelect { shared_fields, .. } => shared_fields,
+            TableField::MultipleSelect { shared_fields, .. } => shared_fields,
+            TableField::PhoneNumber { shared_fields, .. } => shared_fields,
+            TableField::Formula { shared_fields, .. } => shared_fields,
+            TableField::Count { shared_fields, .. } => shared_fields,
+            TableField::Rollup { shared_fields, .. } => shared_fields,
+            TableField::Lookup { shared_fields, .. } => shared_fields,
+            TableField::MultipleCollaborators { shared_fields, .. } => shared_fields,
+            TableField::Uuid { shared_fields, .. } => shared_fields,
+            TableField::AutoNumber { shared_fields, .. } => shared_fields,
+            TableField::Password { shared_fields, .. } => shared_fields,
+            TableField::Ai { shared_fields, .. } => shared_fields,
+        }
+    }
+
     pub fn get_original_name(&self) -> &String {
         match self {
             TableField::Text { shared_fields, .. } => &shared_fields.name,
@@ -342,7 +580,149 @@ impl TableField {
         }
     }
 
-    #[allow(dead_code)]
+    /// The type of the field as Baserow calls it.
+    pub fn get_baserow_type(&self) -> &'static str {
+        match self {
+            TableField::Text { .. } => "text",
+            TableField::LongText { .. } => "long_text",
+            TableField::Url { .. } => "url",
+            TableField::Email { .. } => "email",
+            TableField::Number { .. } => "number",
+            TableField::Rating { .. } => "rating",
+            TableField::Boolean { .. } => "boolean",
+            TableField::Date { .. } => "date",
+            TableField::LastModified { .. } => "last_modified",
+            TableField::LastModifiedBy { .. } => "last_modified_by",
+            TableField::CreatedOn { .. } => "created_on",
+            TableField::CreatedBy { .. } => "created_by",
+            TableField::Duration { .. } => "duration",
+            TableField::LinkRow { .. } => "link_row",
+            TableField::File { .. } => "file",
+            TableField::SingleSelect { .. } => "single_select",
+            TableField::MultipleSelect { .. } => "multiple_select",
+            TableField::PhoneNumber { .. } => "phone_number",
+            TableField::Formula { .. } => "formula",
+            TableField::Count { .. } => "count",
+            TableField::Rollup { .. } => "rollup",
+            TableField::Lookup { .. } => "lookup",
+            TableField::MultipleCollaborators { .. } => "multiple_collaborators",
+            TableField::Uuid { .. } => "uuid",
+            TableField::AutoNumber { .. } => "autonumber",
+            TableField::Password { .. } => "password",
+            TableField::Ai { .. } => "ai",
+        }
+    }
+
+    /// Options of select fields and formulas that result in a select.
+    pub fn get_select_options(&self) -> Option<&[SelectOption]> {
+        match self {
+            TableField::SingleSelect { select_options, .. }
+            | TableField::MultipleSelect { select_options, .. } => Some(select_options.as_slice()),
+            TableField::Formula { select_options, .. } => select_options.as_deref(),
+            _ => None,
+        }
+    }
+
+    pub fn get_type_settings(&self) -> TypeSettings {
+        match self {
+            TableField::Number {
+                number_decimal_places,
+                number_negative,
+                ..
+            } => TypeSettings {
+                number_decimal_places: Some(*number_decimal_places as usize),
+                number_negative: Some(*number_negative),
+                ..TypeSettings::default()
+            },
+            TableField::Rating { max_value, .. } => TypeSettings {
+                max_value: Some(*max_value as usize),
+                ..TypeSettings::default()
+            },
+            TableField::Date {
+                date_include_time, ..
+            }
+            | TableField::LastModified {
+                date_include_time, ..
+            }
+            | TableField::CreatedOn {
+                date_include_time, ..
+            } => TypeSettings {
+                date_include_time: Some(*date_include_time),
+                ..TypeSettings::default()
+            },
+            TableField::Formula {
+                formula_type,
+                array_formula_type,
+                number_decimal_places,
+                date_include_time,
+                ..
+            }
+            | TableField::Rollup {
+                formula_type,
+                array_formula_type,
+                number_decimal_places,
+                date_include_time,
+                ..
+            } => TypeSettings {
+                formula_type: Some(formula_type.clone()),
+                array_formula_type: array_formula_type.clone(),
+                number_decimal_places: number_decimal_places.map(|places| places as usize),
+                date_include_time: *date_include_time,
+                ..TypeSettings::default()
+            },
+            _ => TypeSettings::default(),
+        }
+    }
+
+    /// Doc comment for the struct field generated for this field, describing the field in
+    /// Baserow.
+    pub fn get_doc(&self) -> TokenStream {
+        let mut lines = Vec::new();
+        if let Some(description) = self
+            .get_description()
+            .as_deref()
+            .map(str::trim)
+            .filter(|description| !description.is_empty())
+        {
+            // Indented lines would turn into code blocks, which rustdoc runs as tests
+            lines.extend(
+                description
+                    .lines()
+                    .map(|line| line.trim_start().to_string()),
+            );
+            lines.push(String::new());
+        }
+        lines.push(format!(
+            "Baserow field `{}` (id {}) of type `{}`.",
+            self.get_original_name(),
+            self.get_id(),
+            self.get_baserow_type()
+        ));
+        if let Some(select_options) = self
+            .get_select_options()
+            .filter(|options| !options.is_empty())
+        {
+            lines.push(String::new());
+            lines.push(format!(
+                "Options: {}",
+                select_options
+                    .iter()
+                    .map(|option| format!("`{}`", option.value))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ));
+        }
+
+        // The leading space is what `///` comments have as well
+        let lines = lines.iter().map(|line| match line.is_empty() {
+            true => String::new(),
+            false => format!(" {}", line),
+        });
+        quote! {
+            #(#[doc = #lines])*
+        }
+    }
+
     pub fn get_description(&self) -> &Option<String> {
         match self {
             TableField::Text { shared_fields, .. } => &shared_fields.description,
@@ -375,35 +755,102 @@ impl TableField {
         }
     }
 
+    // Name for types that are generated specifically for this field
+    // Follows renames, so that fields with colliding names get enums with different names too
+    fn get_type_name(&self, table_name: &str) -> String {
+        if let Some(type_name) = &self.get_shared_fields().options.type_name {
+            return type_name.clone();
+        }
+        let field_name = match &self.get_shared_fields().options.rename {
+            Some(rename) => rename,
+            None => self.get_original_name(),
+        };
+        to_identifier(&format!("{} {}", table_name, field_name), Pascal)
+    }
+
+    /// Pick the names of the types generated for this field from `type_names`, which holds the
+    /// names of all types generated into the same module.
+    pub fn name_types(&mut self, table_name: &str, type_names: &mut Identifiers) {
+        // A custom type replaces anything we would generate for the field
+        if self.get_shared_fields().options.rust_type.is_some() {
+            return;
+        }
+        let generates_type = match self {
+            TableField::SingleSelect { .. } | TableField::MultipleCollaborators { .. } => true,
+            TableField::Formula { formula_type, .. } => formula_type == "single_select",
+            TableField::LinkRow {
+                link_row_table_primary_field,
+                ..
+            } => {
+                link_row_table_primary_field.name_types(table_name, type_names);
+                false
+            }
+            _ => false,
+        };
+        if generates_type {
+            let mut options = self.get_options().clone();
+            options.type_name = Some(type_names.unique(self.get_type_name(table_name)));
+            self.set_options(options);
+        }
+    }
+
     pub fn get_extra_structs(&self, table_name: &str) -> Option<TokenStream> {
-        match self {
+        let patch_adapter = self.get_patch_adapter(table_name);
+        // A custom type replaces anything we would generate for the field
+        if self.get_shared_fields().options.rust_type.is_some() {
+            return patch_adapter;
+        }
+        let extra_structs = match self {
             TableField::SingleSelect { select_options, .. } => {
+                Some(select_enum(&self.get_type_name(table_name), select_options))
+            }
+            TableField::Formula {
+                formula_type,
+                select_options,
+                ..
+            } if formula_type.eq("single_select") => Some(select_enum(
+                &self.get_type_name(table_name),
+                select_options.as_deref().unwrap_or_default(),
+            )),
+            TableField::MultipleCollaborators {
+                available_collaborators,
+                ..
+            } => {
+                let rust_name = format_ident!("{}", self.get_type_name(table_name));
                 let mut variants = TokenStream::new();
-                for option in select_options {
-                    let serialized_name = &option.value;
+                let mut conversions = TokenStream::new();
+                let mut identifiers = Identifiers::new(Pascal);
+                for collaborator in available_collaborators {
                     let rust_variant_name = format_ident!(
                         "{}",
-                        cleanup_name(&serialized_name.to_case(Pascal)).replace(' ', "")
+                        identifiers.unique(to_identifier(&collaborator.name, Pascal))
                     );
+                    let id = collaborator.id as usize;
+                    let name = &collaborator.name;
                     variants.extend(quote! {
-                        #[serde(rename = #serialized_name)]
-                        #[strum(serialize = #serialized_name)]
-                        #rust_variant_name {color: String, id: usize},
+                        #rust_variant_name,
+                    });
+                    conversions.extend(quote! {
+                        #rust_name::#rust_variant_name => Collaborator {
+                            id: #id,
+                            name: #name.to_string(),
+                        },
                     });
                 }
 
-                let rust_name = cleanup_name(&format!(
-                    "{}{}",
-                    table_name.to_case(Pascal),
-                    self.get_original_name().to_case(Pascal)
-                ));
-                let rust_name = format_ident!("{}", rust_name);
                 Some(quote! {
-                    #[derive(Serialize, Deserialize, Debug, Clone, EnumString, Display)]
-                    #[serde(tag = "value")]
+                    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
                     pub enum #rust_name {
                         #variants
                     }
+
+                    impl From<#rust_name> for Collaborator {
+                        fn from(value: #rust_name) -> Self {
+                            match value {
+                                #conversions
+                            }
+                        }
+                    }
                 })
             }
             TableField::LinkRow {
@@ -411,74 +858,338 @@ impl TableField {
                 ..
             } => link_row_table_primary_field.get_extra_structs(table_name),
             _ => None,
+        };
+        match patch_adapter {
+            Some(patch_adapter) => Some(quote! {
+                #extra_structs
+                #patch_adapter
+            }),
+            None => extra_structs,
         }
     }
 
-    pub fn get_deserializer(&self) -> Option<TokenStream> {
+    // Patches wrap values in another Option, which a custom serde module doesn't know about
+    fn get_patch_adapter(&self, table_name: &str) -> Option<TokenStream> {
+        let module = self.get_shared_fields().options.serde_with.as_ref()?;
+        if self.is_read_only() {
+            return None;
+        }
+        let module = syn::parse_str::<syn::Path>(module).unwrap();
+        let fn_name = format_ident!("{}", self.get_patch_adapter_name(table_name));
+        let field_type = syn::parse_str::<syn::Type>(&self.get_rust_type(table_name)).unwrap();
+        Some(quote! {
+            fn #fn_name<S>(
+                value: &Option<Option<#field_type>>,
+                serializer: S,
+            ) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                match value {
+                    Some(value) => #module::serialize(value, serializer),
+                    None => serializer.serialize_none(),
+                }
+            }
+        })
+    }
+
+    fn get_patch_adapter_name(&self, table_name: &str) -> String {
+        format!(
+            "patch_{}",
+            self.get_type_name(table_name).to_case(Case::Snake)
+        )
+    }
+
+    pub fn get_helper_fns(&self) -> Option<TokenStream> {
+        if self.get_shared_fields().options.rust_type.is_some() {
+            return None;
+        }
+        match self {
+            TableField::Duration {
+                duration_format, ..
+            } => {
+                let field_name = format_ident!("{}", self.get_name());
+                let fn_name = format_ident!("{}_formatted", self.get_name());
+                Some(quote! {
+                    pub fn #fn_name(&self) -> Option<String> {
+                        self.#field_name
+                            .as_ref()
+                            .map(|duration| format_duration(duration, #duration_format))
+                    }
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Statements checking `value`, a reference to the value of this field, against the
+    /// configuration of the field in Baserow, pushing a `Violation` to `violations` for every
+    /// problem found.
+    /// Ratings and selects are mostly covered by their types, so there is less to check for them.
+    pub fn get_validation(&self, table_name: &str) -> Option<TokenStream> {
+        // Nothing is known about custom types
+        if self.get_shared_fields().options.rust_type.is_some() {
+            return None;
+        }
+        let field_name = self.get_name();
         match self {
             TableField::Number {
+                shared_fields,
                 number_decimal_places,
                 number_negative,
                 ..
-            } => {
-                if number_decimal_places.gt(&0) {
-                    Some(quote! {, deserialize_with = "float_or_null"})
-                } else if *number_negative {
-                    Some(quote! {, deserialize_with = "isize_or_null"})
+            } if number_decimal_places.gt(&0) => {
+                let decimal_places = *number_decimal_places as u32;
+                let message = format!("must not have more than {} decimal places", decimal_places);
+                let (decimal_check, negative) = if shared_fields.options.exact_decimals {
+                    (
+                        quote! { value.normalize().scale() > #decimal_places },
+                        quote! { value.is_sign_negative() && !value.is_zero() },
+                    )
                 } else {
-                    Some(quote! {, deserialize_with = "usize_or_null"})
+                    (
+                        quote! { exceeds_decimal_places(*value, #decimal_places) },
+                        quote! { *value < 0.0 },
+                    )
+                };
+                let negative_check = (!number_negative).then(|| {
+                    quote! {
+                        if #negative {
+                            violations.push(Violation::new(#field_name, "must not be negative"));
+                        }
+                    }
+                });
+                Some(quote! {
+                    if #decimal_check {
+                        violations.push(Violation::new(#field_name, #message));
+                    }
+                    #negative_check
+                })
+            }
+            // The dedicated types can't hold invalid values in the first place
+            TableField::Url { shared_fields }
+            | TableField::Email { shared_fields }
+            | TableField::PhoneNumber { shared_fields }
+                if shared_fields.options.typed_strings =>
+            {
+                None
+            }
+            TableField::Url { .. } => Some(quote! {
+                if !is_valid_url(value) {
+                    violations.push(Violation::new(#field_name, "is not a valid url"));
                 }
+            }),
+            TableField::Email { .. } => Some(quote! {
+                if !is_valid_email(value) {
+                    violations.push(Violation::new(#field_name, "is not a valid email address"));
+                }
+            }),
+            TableField::PhoneNumber { .. } => Some(quote! {
+                if !is_valid_phone_number(value) {
+                    violations.push(Violation::new(#field_name, "is not a valid phone number"));
+                }
+            }),
+            // The enum ensures the option exists, but the id it carries could still be wrong
+            TableField::SingleSelect { select_options, .. } => {
+                let type_name = format_ident!("{}", self.get_type_name(table_name));
+                let mut arms = TokenStream::new();
+                for (option, variant_name) in select_options
+                    .iter()
+                    .zip(select_variant_names(select_options))
+                {
+                    let option_id = option.id as usize;
+                    let message = format!("option {} must have id {}", option.value, option_id);
+                    arms.extend(quote! {
+                        #type_name::#variant_name { id, .. } if *id != #option_id => {
+                            violations.push(Violation::new(#field_name, #message));
+                        }
+                    });
+                }
+                Some(quote! {
+                    match value {
+                        #arms
+                        _ => {}
+                    }
+                })
+            }
+            _ => None,
+        }
+    }
+
+    // Patches wrap values in another Option, so fields with custom serialization need an adapter
+    pub fn get_patch_serializer(&self, table_name: &str) -> Option<TokenStream> {
+        let options = &self.get_shared_fields().options;
+        if options.serde_with.is_some() {
+            let adapter = self.get_patch_adapter_name(table_name);
+            return Some(quote! {, serialize_with = #adapter});
+        } else if options.rust_type.is_some() {
+            return None;
+        }
+        match self {
+            TableField::Duration { .. } => {
+                Some(quote! {, serialize_with = "patch_duration_as_seconds"})
+            }
+            TableField::Number {
+                shared_fields,
+                number_decimal_places,
+                ..
+            } if shared_fields.options.exact_decimals && number_decimal_places.gt(&0) => {
+                let serializer = format!("patch_decimal_as_string::<{}, _>", number_decimal_places);
+                Some(quote! {, serialize_with = #serializer})
             }
+            _ => None,
+        }
+    }
+
+    pub fn get_deserializer(&self) -> Option<TokenStream> {
+        let options = &self.get_shared_fields().options;
+        if let Some(module) = &options.serde_with {
+            return Some(quote! {, with = #module});
+        } else if options.rust_type.is_some() {
+            return None;
+        }
+        match self {
+            TableField::Number {
+                shared_fields,
+                number_decimal_places,
+                number_negative,
+                ..
+            } => Some(number_deserializer(
+                *number_decimal_places,
+                *number_negative,
+                shared_fields.options.exact_decimals,
+            )),
+            TableField::Date {
+                date_include_time, ..
+            } => Some(date_deserializer(*date_include_time)),
+            TableField::LastModified {
+                date_include_time, ..
+            } => Some(date_deserializer(*date_include_time)),
+            TableField::CreatedOn {
+                date_include_time, ..
+            } => Some(date_deserializer(*date_include_time)),
+            TableField::Duration { .. } => Some(quote! {
+                , deserialize_with = "duration_or_null", serialize_with = "duration_as_seconds"
+            }),
+            TableField::Formula {
+                formula_type,
+                number_decimal_places,
+                date_include_time,
+                ..
+            } => formula_deserializer(formula_type, *number_decimal_places, *date_include_time),
+            TableField::Rollup {
+                formula_type,
+                number_decimal_places,
+                date_include_time,
+                ..
+            } => formula_deserializer(formula_type, *number_decimal_places, *date_include_time),
             TableField::Count { .. } => Some(quote! {, deserialize_with = "usize_or_null"}),
             TableField::AutoNumber { .. } => Some(quote! {, deserialize_with = "usize_or_null"}),
+            TableField::Url { shared_fields }
+            | TableField::Email { shared_fields }
+            | TableField::PhoneNumber { shared_fields }
+            | TableField::Uuid { shared_fields }
+                if shared_fields.options.typed_strings =>
+            {
+                Some(quote! {, deserialize_with = "parse_or_null"})
+            }
             _ => None,
         }
     }
 
     pub fn get_rust_type(&self, table_name: &str) -> String {
+        if let Some(rust_type) = &self.get_shared_fields().options.rust_type {
+            return rust_type.clone();
+        }
         match self {
             TableField::Text { .. } => "String".to_string(),
+            TableField::LongText {
+                long_text_enable_rich_text: Some(true),
+                ..
+            } => "RichText".to_string(),
             TableField::LongText { .. } => "String".to_string(),
+            TableField::Url { shared_fields } if shared_fields.options.typed_strings => {
+                "Url".to_string()
+            }
             TableField::Url { .. } => "String".to_string(),
+            TableField::Email { shared_fields } if shared_fields.options.typed_strings => {
+                "Email".to_string()
+            }
             TableField::Email { .. } => "String".to_string(),
             TableField::Number {
+                shared_fields,
                 number_decimal_places,
                 number_negative,
                 ..
-            } => {
-                if number_decimal_places.gt(&0) {
-                    "f64".to_string()
-                } else if *number_negative {
-                    "isize".to_string()
-                } else {
-                    "usize".to_string()
-                }
-            }
-            TableField::Rating { .. } => "String".to_string(),
+            } => number_rust_type(
+                *number_decimal_places,
+                *number_negative,
+                shared_fields.options.exact_decimals,
+            ),
+            TableField::Rating { max_value, .. } => format!("Rating<{}>", max_value),
             TableField::Boolean { .. } => "bool".to_string(),
-            TableField::Date { .. } => "DateTime".to_string(),
-            TableField::LastModified { .. } => "String".to_string(),
-            TableField::LastModifiedBy { .. } => "String".to_string(),
-            TableField::CreatedOn { .. } => "String".to_string(),
-            TableField::CreatedBy { .. } => "String".to_string(),
-            TableField::Duration { .. } => "Duration".to_string(),
+            TableField::Date {
+                date_include_time, ..
+            } => date_rust_type(*date_include_time),
+            TableField::LastModified {
+                date_include_time, ..
+            } => date_rust_type(*date_include_time),
+            TableField::LastModifiedBy { .. } => "Collaborator".to_string(),
+            TableField::CreatedOn {
+                date_include_time, ..
+            } => date_rust_type(*date_include_time),
+            TableField::CreatedBy { .. } => "Collaborator".to_string(),
+            TableField::Duration { .. } => "TimeDelta".to_string(),
             TableField::LinkRow {
                 link_row_table_primary_field,
                 ..
             } => link_row_table_primary_field.get_rust_type(table_name),
-            TableField::File { .. } => "String".to_string(),
-            TableField::SingleSelect { .. } => cleanup_name(&format!(
-                "{}{}",
-                table_name.to_case(Pascal),
-                self.get_original_name().to_case(Pascal)
-            )),
+            TableField::File { .. } => "Vec<BaserowFile>".to_string(),
+            TableField::SingleSelect { .. } => self.get_type_name(table_name),
             TableField::MultipleSelect { .. } => "String".to_string(),
+            TableField::PhoneNumber { shared_fields } if shared_fields.options.typed_strings => {
+                "PhoneNumber".to_string()
+            }
             TableField::PhoneNumber { .. } => "String".to_string(),
-            TableField::Formula { .. } => "String".to_string(),
+            TableField::Formula {
+                formula_type,
+                array_formula_type,
+                number_decimal_places,
+                date_include_time,
+                ..
+            } => match formula_type.as_str() {
+                "single_select" => self.get_type_name(table_name),
+                _ => formula_rust_type(
+                    formula_type,
+                    array_formula_type.as_deref(),
+                    *number_decimal_places,
+                    *date_include_time,
+                ),
+            },
             TableField::Count { .. } => "usize".to_string(),
-            TableField::Rollup { .. } => "String".to_string(),
-            TableField::Lookup { .. } => "String".to_string(),
-            TableField::MultipleCollaborators { .. } => "String".to_string(),
+            TableField::Rollup {
+                formula_type,
+                array_formula_type,
+                number_decimal_places,
+                date_include_time,
+                ..
+            } => formula_rust_type(
+                formula_type,
+                array_formula_type.as_deref(),
+                *number_decimal_places,
+                *date_include_time,
+            ),
+            TableField::Lookup { target, .. } => format!(
+                "Vec<LookupValue<{}>>",
+                match target {
+                    Some(target) => target.field.get_lookup_value_type(&target.table_name),
+                    None => "serde_json::Value".to_string(),
+                }
+            ),
+            TableField::MultipleCollaborators { .. } => "Vec<Collaborator>".to_string(),
+            TableField::Uuid { shared_fields } if shared_fields.options.typed_strings => {
+                "Uuid".to_string()
+            }
             TableField::Uuid { .. } => "String".to_string(),
             TableField::AutoNumber { .. } => "usize".to_string(),
             TableField::Password { .. } => "String".to_string(),
@@ -486,6 +1197,94 @@ impl TableField {
         }
     }
 
+    // Type of the values when this field is the target of a lookup
+    fn get_lookup_value_type(&self, table_name: &str) -> String {
+        match self {
+            // Durations are sent as seconds, which TimeDelta has no serde representation for
+            TableField::Duration { .. } => "f64".to_string(),
+            // These already are arrays of values, looking them up nests the arrays in ways
+            // we can't sensibly type
+            TableField::LinkRow { .. } | TableField::Lookup { .. } => {
+                "serde_json::Value".to_string()
+            }
+            TableField::Formula { formula_type, .. } if formula_type.eq("array") => {
+                "serde_json::Value".to_string()
+            }
+            _ => self.get_rust_type(table_name),
+        }
+    }
+
+    /// Name of the field in rows and filters sent to and received from the api.
+    pub fn get_api_name(&self) -> String {
+        if self.get_options().user_field_names {
+            self.get_original_name().to_string()
+        } else {
+            format!("field_{}", self.get_id())
+        }
+    }
+
+    pub fn get_options(&self) -> &FieldOptions {
+        &self.get_shared_fields().options
+    }
+
+    pub fn set_options(&mut self, options: FieldOptions) {
+        self.get_shared_fields_mut().options = options;
+    }
+
+    /// Attach the definition of the field this lookup field retrieves its values from, the api
+    /// only references it by id.
+    /// `fields` maps field ids to the field and the name of the table containing it.
+    pub fn resolve_lookup_target(&mut self, fields: &HashMap<usize, (String, TableField)>) {
+        if let TableField::Lookup {
+            target_field_id: Some(target_field_id),
+            target,
+            ..
+        } = self
+        {
+            *target = fields
+                .get(&(*target_field_id as usize))
+                .map(|(table_name, field)| LookupTarget {
+                    table_name: table_name.clone(),
+                    field: Box::new(field.clone()),
+                });
+        }
+    }
+
+    /// Forget the target of this lookup field if its values would be typed with a type that is
+    /// generated for the target field, and that field isn't in `generated_fields`.
+    /// The values are left untyped then, like for lookups whose target is unknown.
+    pub fn drop_lookup_target(&mut self, generated_fields: &HashSet<usize>) {
+        if let TableField::Lookup { target, .. } = self
+            && target.as_ref().is_some_and(|target| {
+                target.field.has_generated_type()
+                    && !generated_fields.contains(&target.field.get_id())
+            })
+        {
+            *target = None;
+        }
+    }
+
+    // Whether the rust type of this field is generated along with its table, like the enums of
+    // single selects
+    fn has_generated_type(&self) -> bool {
+        if self.get_options().rust_type.is_some() {
+            return false;
+        }
+        match self {
+            TableField::SingleSelect { .. } => true,
+            TableField::Formula { formula_type, .. } => formula_type.eq("single_select"),
+            _ => false,
+        }
+    }
+
+    /// Whether Baserow computes the value of this field, these cannot be written by the client.
+    pub fn is_read_only(&self) -> bool {
+        match self {
+            TableField::Formula { .. } | TableField::Rollup { .. } => true,
+            _ => self.get_shared_fields().read_only,
+        }
+    }
+
     pub fn is_primary(&self) -> bool {
         match self {
             TableField::Text { shared_fields, .. } => shared_fields.primary,
@@ -553,9 +1352,10 @@ impl TableField {
 
 #[cfg(test)]
 mod tests {
-    use crate::field_types::TableField;
+    use crate::field_types::{FieldOptions, TableField, formula_rust_type};
     use convert_case::Case::Pascal;
-    use convert_case::{Case, Casing};
+    use convert_case::Casing;
+    use std::collections::HashMap;
     use std::fs;
 
     #[test]
@@ -565,12 +1365,164 @@ mod tests {
 
         let json: Vec<TableField> =
             serde_json::from_str(&contents).expect("file should be proper JSON");
+        assert!(!json.is_empty());
 
         let contents = fs::read_to_string("testdata/field_types2.json")
             .expect("Should have been able to read the file");
 
         let json: Vec<TableField> =
             serde_json::from_str(&contents).expect("file should be proper JSON");
+        assert!(!json.is_empty());
+    }
+
+    fn load_field(file: &str, name: &str) -> TableField {
+        let contents = fs::read_to_string(file).expect("Should have been able to read the file");
+        serde_json::from_str::<Vec<TableField>>(&contents)
+            .expect("file should be proper JSON")
+            .into_iter()
+            .find(|field| field.get_original_name().eq(name))
+            .expect("field should exist in test data")
+    }
+
+    #[test]
+    fn test_date_types() {
+        let deadline = load_field("testdata/field_types1.json", "Deadline");
+        assert_eq!(deadline.get_rust_type("Projects"), "NaiveDate");
+
+        let kickoff = load_field("testdata/field_types1.json", "Kickoff");
+        assert_eq!(kickoff.get_rust_type("Projects"), "DateTime<Utc>");
+
+        let created_on = load_field("testdata/field_types1.json", "Created on");
+        assert_eq!(created_on.get_rust_type("Projects"), "DateTime<Utc>");
+    }
+
+    #[test]
+    fn test_collaborator_types() {
+        let created_by = load_field("testdata/field_types1.json", "Created by");
+        assert_eq!(created_by.get_rust_type("Projects"), "Collaborator");
+
+        let owners = load_field("testdata/field_types1.json", "Owners");
+        assert_eq!(owners.get_rust_type("Projects"), "Vec<Collaborator>");
+        let extra_structs = owners.get_extra_structs("Projects").unwrap().to_string();
+        assert!(extra_structs.contains("pub enum ProjectsOwners { JaneDoe , MaxMustermann , }"));
+    }
+
+    #[test]
+    fn test_formula_types() {
+        let total = load_field("testdata/field_types1.json", "Total");
+        assert_eq!(total.get_rust_type("Projects"), "f64");
+        assert!(total.is_read_only());
+
+        let is_done = load_field("testdata/field_types1.json", "Is done");
+        assert_eq!(is_done.get_rust_type("Projects"), "bool");
+
+        let revenue = load_field("testdata/field_types1.json", "Customer revenue");
+        assert_eq!(revenue.get_rust_type("Projects"), "f64");
+        assert!(revenue.is_read_only());
+
+        assert_eq!(formula_rust_type("text", None, None, None), "String");
+        for formula_type in [
+            "link",
+            "button",
+            "multiple_select",
+            "single_file",
+            "invalid",
+        ] {
+            assert_eq!(
+                formula_rust_type(formula_type, None, None, None),
+                "serde_json::Value"
+            );
+        }
+    }
+
+    #[test]
+    fn test_lookup_types() {
+        let mut city = load_field("testdata/field_types1.json", "Customer city");
+        assert_eq!(
+            city.get_rust_type("Projects"),
+            "Vec<LookupValue<serde_json::Value>>"
+        );
+
+        let target = load_field("testdata/field_types2.json", "City");
+        let fields = HashMap::from([(target.get_id(), ("Customers".to_string(), target))]);
+        city.resolve_lookup_target(&fields);
+        assert_eq!(city.get_rust_type("Projects"), "Vec<LookupValue<String>>");
+    }
+
+    #[test]
+    fn test_rating_type() {
+        let priority = load_field("testdata/field_types1.json", "Priority");
+        assert_eq!(priority.get_rust_type("Projects"), "Rating<5>");
+    }
+
+    #[test]
+    fn test_exact_decimals() {
+        let mut budget = load_field("testdata/field_types1.json", "Budget");
+        assert_eq!(budget.get_rust_type("Projects"), "f64");
+
+        budget.set_options(FieldOptions {
+            exact_decimals: true,
+            ..Default::default()
+        });
+        assert_eq!(budget.get_rust_type("Projects"), "Decimal");
+        assert!(
+            budget
+                .get_deserializer()
+                .unwrap()
+                .to_string()
+                .contains("decimal_as_string::<2, _>")
+        );
+
+        // Numbers without decimal places stay integers
+        let mut effort = load_field("testdata/field_types1.json", "Effort");
+        effort.set_options(FieldOptions {
+            exact_decimals: true,
+            ..Default::default()
+        });
+        assert_ne!(effort.get_rust_type("Projects"), "Decimal");
+    }
+
+    #[test]
+    fn test_doc() {
+        let name = load_field("testdata/field_types1.json", "Name");
+        let doc = name.get_doc().to_string();
+        assert!(doc.contains("\" Name of the project\""));
+        assert!(doc.contains("\" Baserow field `Name` (id 1001) of type `text`.\""));
+
+        let status = load_field("testdata/field_types1.json", "Status");
+        assert!(
+            status
+                .get_doc()
+                .to_string()
+                .contains("\" Options: `In progress`, `Done`\"")
+        );
+    }
+
+    #[test]
+    fn test_rich_text_type() {
+        let notes = load_field("testdata/field_types1.json", "Notes");
+        assert_eq!(notes.get_rust_type("Projects"), "RichText");
+    }
+
+    #[test]
+    fn test_typed_strings() {
+        let typed = FieldOptions {
+            typed_strings: true,
+            ..Default::default()
+        };
+        for (name, rust_type) in [
+            ("Website", "Url"),
+            ("Contact Email", "Email"),
+            ("Phone", "PhoneNumber"),
+            ("Reference", "Uuid"),
+        ] {
+            let mut field = load_field("testdata/field_types1.json", name);
+            assert_eq!(field.get_rust_type("Projects"), "String");
+
+            field.set_options(typed.clone());
+            assert_eq!(field.get_rust_type("Projects"), rust_type);
+            assert!(field.get_validation("Projects").is_none());
+        }
     }
 
     #[test]
@@ -1,38 +1,150 @@
-use crate::baserow_config::Database;
-use crate::field_types::{TableField, cleanup_name};
+use crate::baserow_config::{Database, Override};
+use crate::field_types::{FieldOptions, TableField};
+use crate::identifier::{Identifiers, to_identifier};
+use convert_case::Case;
 use convert_case::Case::Snake;
-use convert_case::{Case, Casing};
+use proc_macro2::Literal;
 use quote::__private::TokenStream;
-use quote::{format_ident, quote};
-use reqwest::Client as ReqwestClient;
-use reqwest::header::{ACCEPT, AUTHORIZATION, HeaderMap, HeaderValue};
+use quote::{ToTokens, format_ident, quote};
+use reqwest::header::{ACCEPT, AUTHORIZATION, HeaderMap, HeaderValue, InvalidHeaderValue};
+use reqwest::{Client as ReqwestClient, Response};
 use serde::{Deserialize, Serialize};
-use std::fmt::format;
-use std::fs::File;
-use std::io::Write;
-use std::path::Path;
-use convert_case::pattern::lowercase;
+use snafu::{ResultExt, Snafu};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::{fs, io};
 
-static LIST_TABLES_URL: &str = "https://api.baserow.io/api/database/tables/all-tables/";
-static LIST_TABLE_FIELDS_URL: &str = "https://api.baserow.io/api/database/fields/table/";
+// The default url for Baserow cloud
+static CLOUD_URL: &str = "https://api.baserow.io";
+static LIST_TABLES_PATH: &str = "/api/database/tables/all-tables/";
+static LIST_TABLE_FIELDS_PATH: &str = "/api/database/fields/table/";
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct BaserowConfig {
-    token: String,
-    database: usize,
+// Types the generated code imports or generates once per database, tables can't use these names
+const RESERVED_TYPE_NAMES: &[&str] = &[
+    "Option",
+    "Result",
+    "String",
+    "Vec",
+    "Box",
+    "BaserowObject",
+    "BaserowWriteObject",
+    "Identifier",
+    "Visitor",
+    "Deserialize",
+    "Deserializer",
+    "Serialize",
+    "Serializer",
+    "Write",
+    "FromStr",
+    "ToString",
+    "Display",
+    "EnumString",
+    "DateTime",
+    "NaiveDate",
+    "Utc",
+    "BaserowFile",
+    "RichText",
+    "Trackable",
+    "Validate",
+    "Violation",
+    "TimeDelta",
+    "Decimal",
+    "Email",
+    "PhoneNumber",
+    "Url",
+    "Uuid",
+    "Collaborator",
+    "Rating",
+    "RatingOutOfRange",
+    "LookupValue",
+];
+
+// Modules and crates the generated code imports, tables can't use these as module names when
+// every table gets its own file
+const RESERVED_MODULE_NAMES: &[&str] = &[
+    "std",
+    "core",
+    "alloc",
+    "fmt",
+    "de",
+    "serde",
+    "strum_macros",
+    "chrono",
+    "baserow_client",
+    "rust_decimal",
+    "uuid",
+];
+
+// Marks the files the generator wrote, only these are removed when regenerating
+const GENERATED_HEADER: &str =
+    "// Generated by baserow-codegen, changes are lost when the code is regenerated.\n\n";
+
+#[derive(Snafu, Debug)]
+pub enum Error {
+    #[snafu(display("Token is not a valid header value: {source}"))]
+    InvalidToken { source: InvalidHeaderValue },
+    #[snafu(display("Could not create http client: {source}"))]
+    CreateClient { source: reqwest::Error },
+    #[snafu(display("Could not read tables and fields from [{url}]: {source}"))]
+    FetchSchema { source: reqwest::Error, url: String },
+    #[snafu(display("Could not read schema file [{path}]: {source}"))]
+    ReadSchema { source: io::Error, path: String },
+    #[snafu(display("Error parsing schema file [{path}]: {source}"))]
+    ParseSchema {
+        source: serde_json::Error,
+        path: String,
+    },
+    #[snafu(display("Error serializing schema: {source}"))]
+    SerializeSchema { source: serde_json::Error },
+    #[snafu(display(
+        "No schema to generate code from, create the generator with `Generator::from_schema`"
+    ))]
+    MissingSchema {},
+    #[snafu(display("Could not create directory [{path}]: {source}"))]
+    CreateDir { source: io::Error, path: String },
+    #[snafu(display("Could not remove previously generated [{path}]: {source}"))]
+    RemoveGenerated { source: io::Error, path: String },
+    #[snafu(display("Generated invalid code for [{path}]: {source}"))]
+    ParseGenerated { source: syn::Error, path: String },
+    #[snafu(display("Could not write file [{path}]: {source}"))]
+    WriteFile { source: io::Error, path: String },
 }
 
 pub struct Generator {
-    client: ReqwestClient,
+    source: Source,
+    base_url: String,
+    field_options: FieldOptions,
+    overrides: Vec<Override>,
+    file_per_table: bool,
+    selected_databases: Option<Vec<usize>>,
+}
+
+// Where the tables and fields to generate code for come from
+enum Source {
+    Api(ReqwestClient),
+    Snapshot(Schema),
+}
+
+/// The tables and fields of the configured databases, as Baserow returned them.
+///
+/// A schema that was saved as JSON allows generating code without access to Baserow, for
+/// example from a `build.rs`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Schema {
+    databases: Vec<Database>,
+    tables: Vec<Table>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 struct Table {
     pub id: usize,
     pub name: String,
     pub order: usize,
     pub database_id: usize,
     pub fields: Option<Vec<TableField>>,
+    // Not part of the api response, set from the overrides in the configuration
+    #[serde(skip)]
+    pub rename: Option<String>,
 }
 
 #[allow(dead_code)]
@@ -55,194 +167,784 @@ impl Identifier {
     }
 }
 
+impl Schema {
+    pub fn from_file(path: &Path) -> Result<Self, Error> {
+        let path_name = path.display().to_string();
+        serde_json::from_str(
+            &fs::read_to_string(path).context(ReadSchemaSnafu { path: &path_name })?,
+        )
+        .context(ParseSchemaSnafu { path: &path_name })
+    }
+
+    pub fn to_json(&self) -> Result<String, Error> {
+        serde_json::to_string_pretty(self).context(SerializeSchemaSnafu)
+    }
+
+    pub fn get_databases(&self) -> &[Database] {
+        &self.databases
+    }
+
+    /// Ids and names of the tables of the database with the id `database_id`, ordered by their
+    /// id.
+    pub fn get_tables(&self, database_id: usize) -> Vec<(usize, &str)> {
+        self.tables
+            .iter()
+            .filter(|table| table.database_id == database_id)
+            .map(|table| (table.id, table.name.as_str()))
+            .collect()
+    }
+}
+
 impl Table {
     pub fn extend_with_fields(&mut self, fields: Vec<TableField>) {
         self.fields = Some(fields);
     }
 
     pub fn get_struct_name(&self) -> String {
-        self.name.to_case(Case::Pascal)
+        match &self.rename {
+            Some(rename) => rename.clone(),
+            None => to_identifier(&self.name, Case::Pascal),
+        }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-struct SearchResult<T> {
-    pub count: usize,
-    pub next: Option<String>,
-    pub previous: Option<String>,
-    pub results: Vec<T>,
-}
-
-#[derive(Serialize, Deserialize, Debug)]
-struct IdOnly {
-    pub id: usize,
-}
-
 impl Generator {
-    pub fn new(token: &str) -> Self {
+    pub fn new(token: &str) -> Result<Self, Error> {
         let mut default_headers = HeaderMap::new();
         default_headers.insert(
             AUTHORIZATION,
-            HeaderValue::from_str(&format!("Token {}", token)).unwrap(),
+            HeaderValue::from_str(&format!("Token {}", token)).context(InvalidTokenSnafu)?,
         );
-        default_headers.insert(ACCEPT, HeaderValue::from_str("application/json").unwrap());
+        default_headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
+
+        Ok(Self {
+            source: Source::Api(
+                ReqwestClient::builder()
+                    .default_headers(default_headers)
+                    .build()
+                    .context(CreateClientSnafu)?,
+            ),
+            base_url: CLOUD_URL.to_string(),
+            field_options: FieldOptions::default(),
+            overrides: Vec::new(),
+            file_per_table: false,
+            selected_databases: None,
+        })
+    }
 
+    /// Generate code from a schema that was saved earlier instead of reading it from Baserow.
+    pub fn from_schema(schema: Schema) -> Self {
         Self {
-            client: ReqwestClient::builder()
-                .default_headers(default_headers)
-                .build()
-                .unwrap(),
+            source: Source::Snapshot(schema),
+            base_url: CLOUD_URL.to_string(),
+            field_options: FieldOptions::default(),
+            overrides: Vec::new(),
+            file_per_table: false,
+            selected_databases: None,
+        }
+    }
+
+    /// Read the schema from a self hosted Baserow instead of Baserow cloud.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Generate `rust_decimal::Decimal` instead of `f64` for number fields with decimal places,
+    /// the generated code then needs `rust_decimal` as a dependency.
+    pub fn with_exact_decimals(mut self, exact_decimals: bool) -> Self {
+        self.field_options.exact_decimals = exact_decimals;
+        self
+    }
+
+    /// Generate `Email`, `Url`, `PhoneNumber` and `uuid::Uuid` instead of `String` for the
+    /// corresponding fields, the generated code then needs `uuid` with its `serde` feature as a
+    /// dependency.
+    pub fn with_typed_strings(mut self, typed_strings: bool) -> Self {
+        self.field_options.typed_strings = typed_strings;
+        self
+    }
+
+    /// Have the generated structs refer to fields by their names in Baserow instead of
+    /// `field_<id>`, the client then requests rows of these structs with `user_field_names`.
+    pub fn with_user_field_names(mut self, user_field_names: bool) -> Self {
+        self.field_options.user_field_names = user_field_names;
+        self
+    }
+
+    /// Write every table into a file of its own in a directory per database, instead of one
+    /// file per database.
+    pub fn with_file_per_table(mut self, file_per_table: bool) -> Self {
+        self.file_per_table = file_per_table;
+        self
+    }
+
+    /// Replace types, serde modules or names of specific tables and fields.
+    pub fn with_overrides(mut self, overrides: Vec<Override>) -> Self {
+        self.overrides = overrides;
+        self
+    }
+
+    /// Only generate the code of the databases with these ids, the files of the other databases
+    /// are left as they are. `mod.rs` still lists the modules of all databases.
+    pub fn with_selected_databases(mut self, database_ids: Vec<usize>) -> Self {
+        self.selected_databases = Some(database_ids);
+        self
+    }
+
+    fn is_selected(&self, database: &Database) -> bool {
+        self.selected_databases
+            .as_ref()
+            .is_none_or(|ids| ids.contains(&database.id))
+    }
+
+    /// Read the tables and fields of `databases` from Baserow, a generator created from a schema
+    /// returns that schema instead.
+    pub async fn fetch_schema(&self, databases: &[Database]) -> Result<Schema, Error> {
+        match &self.source {
+            Source::Api(client) => {
+                // There is no need to read the tables of databases that aren't generated
+                let selected = databases
+                    .iter()
+                    .filter(|database| self.is_selected(database))
+                    .cloned()
+                    .collect::<Vec<Database>>();
+                Ok(Schema {
+                    databases: databases.to_vec(),
+                    tables: self.list_tables(client, &selected).await?,
+                })
+            }
+            Source::Snapshot(schema) => Ok(schema.clone()),
         }
     }
 
-    async fn list_tables(&self) -> Vec<Table> {
-        let mut tables = self
-            .client
-            .get(LIST_TABLES_URL)
+    async fn list_tables(
+        &self,
+        client: &ReqwestClient,
+        databases: &[Database],
+    ) -> Result<Vec<Table>, Error> {
+        let url = format!("{}{LIST_TABLES_PATH}", self.base_url);
+        let mut tables = client
+            .get(&url)
             .send()
             .await
-            .unwrap()
+            .and_then(Response::error_for_status)
+            .context(FetchSchemaSnafu { url: &url })?
             .json::<Vec<Table>>()
             .await
-            .unwrap();
+            .context(FetchSchemaSnafu { url: &url })?;
+        // The token may have access to more databases than we generate code for
+        tables.retain(|table| {
+            databases
+                .iter()
+                .any(|database| database.id == table.database_id)
+        });
+        // Keeps snapshots and the generated code stable when tables are reordered in Baserow
+        tables.sort_by_key(|table| table.id);
 
         for table in &mut tables {
-            if let Some(table_fields) = self.list_table_fields(&table.id).await {
-                table.extend_with_fields(table_fields);
-            }
+            let table_fields = self.list_table_fields(client, &table.id).await?;
+            table.extend_with_fields(table_fields);
         }
 
-        tables
+        Ok(tables)
     }
 
-    async fn list_table_fields(&self, table_id: &usize) -> Option<Vec<TableField>> {
-        if let Ok(response) = self
-            .client
-            .get(format!("{LIST_TABLE_FIELDS_URL}{table_id}/"))
+    async fn list_table_fields(
+        &self,
+        client: &ReqwestClient,
+        table_id: &usize,
+    ) -> Result<Vec<TableField>, Error> {
+        let url = format!("{}{LIST_TABLE_FIELDS_PATH}{table_id}/", self.base_url);
+        let fields = client
+            .get(&url)
             .send()
             .await
-        {
-            Some(response.json::<Vec<TableField>>().await.unwrap())
-        } else {
-            None
+            .and_then(Response::error_for_status)
+            .context(FetchSchemaSnafu { url: &url })?
+            .json::<Vec<serde_json::Value>>()
+            .await
+            .context(FetchSchemaSnafu { url: &url })?;
+        Ok(parse_fields(*table_id, fields))
+    }
+
+    /// Read the schema of `databases` from Baserow and write the generated code to
+    /// `target_path`.
+    pub async fn generate_structs(
+        &self,
+        databases: &[Database],
+        target_path: &Path,
+    ) -> Result<(), Error> {
+        let schema = self.fetch_schema(databases).await?;
+        self.generate(&schema, target_path)
+    }
+
+    /// Write the code for the schema the generator was created with to `out_dir`, which is
+    /// meant to be called from a `build.rs` with `OUT_DIR`.
+    pub fn generate_to(&self, out_dir: &Path) -> Result<(), Error> {
+        match &self.source {
+            Source::Snapshot(schema) => self.generate(schema, out_dir),
+            Source::Api(_) => MissingSchemaSnafu.fail(),
         }
     }
 
-    pub async fn generate_structs(&self, databases: &Vec<Database>, target_path: &Path) {
-        let mut mod_file = File::create(target_path.join("mod.rs")).expect("Unable to create file");
-        let module_crate_path = target_path.to_str().unwrap().replace("/", "::");
-        // Build tests in mod file along the way
-        let mut tests = TokenStream::new();
+    fn generate(&self, schema: &Schema, target_path: &Path) -> Result<(), Error> {
+        fs::create_dir_all(target_path).context(CreateDirSnafu {
+            path: target_path.display().to_string(),
+        })?;
 
-        // Pull list of all tables accessible with our token, these will be across multiple databases
-        // in order to not do this multiple times we'll filter down to the tables we are interested
-        // in for every iteration below
-        let tablelist = self.list_tables().await;
+        // The schema contains the tables of all databases, we'll filter down to the tables we
+        // are interested in for every iteration below
+        let databases = &schema.databases;
+        let mut tablelist = schema.tables.clone();
+        // Options need to be in place before lookups copy the fields they target
+        apply_field_options(&mut tablelist, &self.field_options, &self.overrides);
+        deduplicate_identifiers(&mut tablelist);
+        resolve_lookup_targets(&mut tablelist);
+        // Filter fields only now, lookups may still target fields that are left out
+        filter_fields(&mut tablelist, databases);
+        drop_missing_lookup_targets(&mut tablelist, databases);
+        // Reordering tables in Baserow shouldn't change the generated code
+        tablelist.sort_by_key(|table| table.id);
 
+        let mut module_names = Identifiers::new(Snake);
+        let mut modules = Vec::new();
         for database in databases {
-            let module_name = cleanup_name(&database.name).to_case(Snake);
-
-            // Write entry for file in mod.rs
-            mod_file
-                .write_all(format!("pub mod {};", module_name).as_bytes())
-                .unwrap();
-            mod_file.write_all("\n".as_bytes()).unwrap();
-
-            let use_clause = format_ident!("crate::{module_crate_path}::{module_name}::*");
-
-            let mut structs = quote! {
-            use baserow_client::client::{BaserowObject, Identifier};
-            use serde::de::Visitor;
-            use serde::{de, Deserialize, Deserializer, Serialize};
-            use std::fmt;
-            use std::fmt::Write;
-            use std::str::FromStr;
-            use std::string::ToString;
-            use strum_macros::{Display, EnumString};
-            use chrono::{DateTime, Local};
-                    };
-
-            // Create module file for this database
-            let mut code_file = File::create(target_path.join(format!("{}.rs", module_name)))
-                .expect("Unable to create file");
+            // Unselected databases are numbered too, so that all databases keep their names
+            let module_name = module_names.unique(to_identifier(&database.name, Snake));
+            if !self.is_selected(database) {
+                modules.push(module_name);
+                continue;
+            }
 
             // Filter list to tables for the database we are looking at in this iteration
-            for table in &tablelist
+            let tables = tablelist
                 .iter()
-                .filter(|t| t.database_id.eq(&database.id))
-                .collect::<Vec<&Table>>()
-            {
-                // Gather information to be used during generation
-                let struct_name = format_ident!("{}", table.get_struct_name());
-                let fields = generate_fields(table.fields.as_ref(), &table.name);
-                let extra_structs =
-                    generate_extra_structs(table.fields.as_ref(), &table.get_struct_name());
-                let primary_field = get_primary_field(table.fields.as_ref());
-                let primary_field_id = format!("field_{}", primary_field.get_id());
-                let primary_id_function = generate_primary_id_fn(primary_field, &table.name);
-                let table_id = table.id;
-
-                // Generate code
-                structs.extend(quote! {
-                    #[derive(Serialize, Deserialize, Debug, Clone)]
-                    pub struct #struct_name {
-                        #fields
+                .filter(|t| t.database_id.eq(&database.id) && database.includes_table(&t.name))
+                .filter(|table| {
+                    let has_primary_field = table
+                        .fields
+                        .iter()
+                        .flatten()
+                        .filter(|f| f.is_primary())
+                        .count()
+                        == 1;
+                    if !has_primary_field {
+                        eprintln!(
+                            "Skipping table [{}], its primary field could not be read",
+                            table.name
+                        );
                     }
+                    has_primary_field
+                })
+                .collect::<Vec<&Table>>();
+
+            remove_database_files(target_path, &module_name)?;
+            for (path, code) in self.generate_database_files(&module_name, &tables) {
+                write_file(&target_path.join(path), code)?;
+            }
+            modules.push(module_name);
+        }
+
+        modules.sort();
+        let modules = modules.iter().map(|module| format_ident!("{}", module));
+        write_file(
+            &target_path.join("mod.rs"),
+            quote! {
+                #(pub mod #modules;)*
+            },
+        )
+    }
+
+    // Paths are relative to the target directory, with one file per table the tables are
+    // private modules that are re-exported, so that the generated types have the same paths
+    // with both layouts
+    fn generate_database_files(
+        &self,
+        module_name: &str,
+        tables: &[&Table],
+    ) -> Vec<(PathBuf, TokenStream)> {
+        let mut shared = self.generate_imports();
+        let mut files = Vec::new();
+        if self.file_per_table {
+            let mut table_modules = Identifiers::new(Snake);
+            table_modules.reserve(RESERVED_MODULE_NAMES);
+            for table in tables {
+                let table_module =
+                    table_modules.unique(to_identifier(&table.get_struct_name(), Snake));
+                let table_ident = format_ident!("{}", table_module);
+                shared.extend(quote! {
+                    mod #table_ident;
+                    pub use #table_ident::*;
+                });
+
+                let table_code = generate_table(table);
+                files.push((
+                    Path::new(module_name).join(format!("{}.rs", table_module)),
+                    quote! {
+                        use super::*;
+
+                        #table_code
+                    },
+                ));
+            }
+        } else {
+            for table in tables {
+                shared.extend(generate_table(table));
+            }
+        }
+
+        shared.extend(generate_shared_types());
+        shared.extend(generate_deserializers());
+        if self.field_options.exact_decimals {
+            shared.extend(generate_decimal_helpers());
+        }
+        if self.field_options.typed_strings {
+            shared.extend(generate_typed_string_helpers());
+        }
+
+        let shared_path = match self.file_per_table {
+            true => Path::new(module_name).join("mod.rs"),
+            false => PathBuf::from(format!("{}.rs", module_name)),
+        };
+        files.insert(0, (shared_path, shared));
+        files
+    }
+
+    // Everything the generated code may need is imported whether the tables use it or not, so
+    // the imports are allowed to be unused for crates that deny warnings
+    fn generate_imports(&self) -> TokenStream {
+        let mut use_trees = vec![
+            quote! { baserow_client::client::{BaserowObject, BaserowWriteObject, Identifier} },
+            quote! { serde::de::Visitor },
+            quote! { serde::{de, Deserialize, Deserializer, Serialize, Serializer} },
+            quote! { std::fmt },
+            quote! { std::fmt::Write },
+            quote! { std::str::FromStr },
+            quote! { std::string::ToString },
+            quote! { strum_macros::{Display, EnumString} },
+            quote! { chrono::{DateTime, NaiveDate, TimeDelta, Utc} },
+            quote! { baserow_client::duration::{duration_as_seconds, duration_or_null, format_duration} },
+            quote! { baserow_client::file::BaserowFile },
+            quote! { baserow_client::rich_text::RichText },
+            quote! { baserow_client::schema::{FieldId, FieldMeta, TableSchema} },
+            quote! { baserow_client::tracked::Trackable },
+            quote! {
+                baserow_client::validation::{
+                    exceeds_decimal_places, is_valid_email, is_valid_phone_number, is_valid_url,
+                    Validate, Violation,
+                }
+            },
+        ];
+        if self.field_options.exact_decimals {
+            use_trees.push(quote! { rust_decimal::Decimal });
+        }
+        if self.field_options.typed_strings {
+            use_trees.push(quote! { baserow_client::contact::{Email, PhoneNumber, Url} });
+            use_trees.push(quote! { uuid::Uuid });
+        }
+        quote! {
+            #(
+                #[allow(unused_imports)]
+                use #use_trees;
+            )*
+        }
+    }
+}
+
+// Files of an earlier run would make the module ambiguous if the layout changed in between, and
+// files of tables that no longer exist would linger
+fn remove_database_files(target_path: &Path, module_name: &str) -> Result<(), Error> {
+    remove_generated(&target_path.join(format!("{}.rs", module_name)))?;
+    remove_generated(&target_path.join(module_name))
+}
+
+// Removes `path` if the generator wrote it, or everything below it that the generator wrote if
+// it is a directory. Code written by hand next to the generated code is never removed
+fn remove_generated(path: &Path) -> Result<(), Error> {
+    let path_name = path.display().to_string();
+    if path.is_dir() {
+        for entry in fs::read_dir(path).context(RemoveGeneratedSnafu { path: &path_name })? {
+            let entry = entry.context(RemoveGeneratedSnafu { path: &path_name })?;
+            remove_generated(&entry.path())?;
+        }
+        let is_empty = fs::read_dir(path)
+            .context(RemoveGeneratedSnafu { path: &path_name })?
+            .next()
+            .is_none();
+        if is_empty {
+            fs::remove_dir(path).context(RemoveGeneratedSnafu { path: &path_name })?;
+        }
+    } else if is_generated(path) {
+        fs::remove_file(path).context(RemoveGeneratedSnafu { path: &path_name })?;
+    }
+    Ok(())
+}
+
+fn is_generated(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|contents| contents.starts_with(GENERATED_HEADER))
+}
+
+fn write_file(path: &Path, code: TokenStream) -> Result<(), Error> {
+    let path_name = path.display().to_string();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context(CreateDirSnafu {
+            path: parent.display().to_string(),
+        })?;
+    }
+    let syntax_tree =
+        syn::parse_file(&code.to_string()).context(ParseGeneratedSnafu { path: &path_name })?;
+    let contents = format!(
+        "{}{}",
+        GENERATED_HEADER,
+        prettyplease::unparse(&syntax_tree)
+    );
+    fs::write(path, contents).context(WriteFileSnafu { path: &path_name })
+}
+
+// All items generated for a single table, the shared types and helpers are generated once
+// per database
+fn generate_table(table: &Table) -> TokenStream {
+    let struct_name = format_ident!("{}", table.get_struct_name());
+    let write_struct_name = format_ident!("{}Write", table.get_struct_name());
+    let fields = generate_fields(table.fields.as_ref(), &table.name);
+    let write_fields = generate_write_fields(table.fields.as_ref(), &table.name);
+    let write_conversion = generate_write_conversion(table.fields.as_ref());
+    let patch_struct_name = format_ident!("{}Patch", table.get_struct_name());
+    let patch_fields = generate_patch_fields(table.fields.as_ref(), &table.name);
+    let patch_setters = generate_patch_setters(table.fields.as_ref(), &table.name);
+    let diff = generate_diff(table.fields.as_ref());
+    let changed_fields = generate_changed_fields(table.fields.as_ref());
+    let updated_on = generate_updated_on(table.fields.as_ref());
+    let all_fields = table.fields.as_deref().unwrap_or_default();
+    let validation = generate_validation(all_fields.iter(), &table.name, false);
+    let write_validation = generate_validation(get_write_fields(all_fields), &table.name, false);
+    let patch_validation = generate_validation(
+        all_fields.iter().filter(|field| !field.is_read_only()),
+        &table.name,
+        true,
+    );
+    let extra_structs = generate_extra_structs(table.fields.as_ref(), &table.name);
+    let helper_fns = generate_helper_fns(table.fields.as_ref());
+    let primary_field = get_primary_field(table.fields.as_ref());
+    let primary_field_id = primary_field.get_api_name();
+    let primary_id_function = generate_primary_id_fn(primary_field, &table.name);
+    // The names in the serde renames are baked in, so the client has to learn from the type
+    // how to request rows
+    let user_field_names = primary_field.get_options().user_field_names;
+    let struct_doc = format!(
+        " A row of the Baserow table `{}` (id {}).",
+        table.name, table.id
+    );
+    let write_struct_doc = format!(
+        " The fields of a row of the Baserow table `{}` (id {}) that can be written.",
+        table.name, table.id
+    );
+    let patch_struct_doc = format!(
+        " Changes to a row of the Baserow table `{}` (id {}), only fields that were set are sent.",
+        table.name, table.id
+    );
+    let table_id = Literal::usize_unsuffixed(table.id);
+    let field_consts = generate_field_consts(table.fields.as_ref());
+
+    quote! {
+        #[doc = #struct_doc]
+        #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+        pub struct #struct_name {
+            #fields
+        }
+
+        #extra_structs
+
+        impl #struct_name {
+            pub const TABLE_ID: usize = #table_id;
+            #field_consts
+
+            #helper_fns
+        }
+
+        impl BaserowObject for #struct_name {
+            fn get_static_table_id() -> usize {
+                Self::TABLE_ID
+            }
+
+            fn get_table_id(&self) -> usize {
+                Self::get_static_table_id()
+            }
+
+            fn get_id(&self) -> Identifier {
+                #primary_id_function
+            }
+
+            fn get_table_id_field(&self) -> String {
+                #primary_field_id.to_string()
+            }
+
+            const USER_FIELD_NAMES: bool = #user_field_names;
+        }
+
+        impl TableSchema for #struct_name {
+            fn get_fields() -> &'static [FieldMeta] {
+                Self::FIELDS
+            }
+        }
+
+        #[doc = #write_struct_doc]
+        #[derive(Serialize, Deserialize, Debug, Clone, Default)]
+        pub struct #write_struct_name {
+            #write_fields
+        }
+
+        impl From<#struct_name> for #write_struct_name {
+            fn from(value: #struct_name) -> Self {
+                Self {
+                    #write_conversion
+                }
+            }
+        }
+
+        impl From<&#struct_name> for #write_struct_name {
+            fn from(value: &#struct_name) -> Self {
+                value.clone().into()
+            }
+        }
+
+        impl BaserowObject for #write_struct_name {
+            fn get_static_table_id() -> usize {
+                #table_id
+            }
+
+            fn get_table_id(&self) -> usize {
+                Self::get_static_table_id()
+            }
+
+            fn get_id(&self) -> Identifier {
+                #primary_id_function
+            }
+
+            fn get_table_id_field(&self) -> String {
+                #primary_field_id.to_string()
+            }
+
+            const USER_FIELD_NAMES: bool = #user_field_names;
+        }
+
+        impl BaserowWriteObject for #write_struct_name {
+            type Read = #struct_name;
+        }
+
+        #[doc = #patch_struct_doc]
+        #[derive(Serialize, Debug, Clone, Default)]
+        pub struct #patch_struct_name {
+            #patch_fields
+        }
+
+        impl #patch_struct_name {
+            #patch_setters
+        }
+
+        impl BaserowWriteObject for #patch_struct_name {
+            type Read = #struct_name;
+        }
+
+        impl Validate for #struct_name {
+            fn validate(&self) -> Vec<Violation> {
+                // Tables without fields to check don't push anything
+                #[allow(unused_mut)]
+                let mut violations = Vec::new();
+                #validation
+                violations
+            }
+        }
+
+        impl Validate for #write_struct_name {
+            fn validate(&self) -> Vec<Violation> {
+                #[allow(unused_mut)]
+                let mut violations = Vec::new();
+                #write_validation
+                violations
+            }
+        }
+
+        impl Validate for #patch_struct_name {
+            fn validate(&self) -> Vec<Violation> {
+                #[allow(unused_mut)]
+                let mut violations = Vec::new();
+                #patch_validation
+                violations
+            }
+        }
+
+        impl Trackable for #struct_name {
+            type Patch = #patch_struct_name;
+
+            fn diff(&self, original: &Self) -> #patch_struct_name {
+                let mut patch = <#patch_struct_name as Default>::default();
+                #diff
+                patch
+            }
+
+            fn changed_fields(&self, other: &Self) -> Vec<&'static str> {
+                let mut changed = Vec::new();
+                #changed_fields
+                changed
+            }
+
+            fn updated_on(&self) -> Option<String> {
+                #updated_on
+            }
+        }
+    }
+}
+
+// Fields are parsed one by one, so that a field type we can't handle only leaves out that field
+// instead of failing the whole generation
+fn parse_fields(table_id: usize, fields: Vec<serde_json::Value>) -> Vec<TableField> {
+    fields
+        .into_iter()
+        .filter_map(|field| {
+            let name = field["name"].as_str().unwrap_or_default().to_string();
+            match serde_json::from_value::<TableField>(field) {
+                Ok(field) => Some(field),
+                Err(e) => {
+                    eprintln!("Skipping field [{name}] of table [{table_id}]: {e}");
+                    None
+                }
+            }
+        })
+        .collect()
+}
 
-                    #extra_structs
+// The primary field is always kept, it is needed to identify rows
+fn filter_fields(tables: &mut [Table], databases: &[Database]) {
+    for table in tables {
+        if let Some(database) = databases.iter().find(|d| d.id.eq(&table.database_id))
+            && let Some(fields) = table.fields.as_mut()
+        {
+            fields.retain(|field| {
+                field.is_primary()
+                    || database.includes_field(&table.name, field.get_original_name())
+            });
+        }
+    }
+}
 
-                    impl BaserowObject for #struct_name {
-                        fn get_static_table_id() -> usize {
-                            #table_id
-                        }
+// Tables and fields are numbered in the order of their ids if their names collide, so that the
+// same schema always results in the same names. Structs come first, the types generated for
+// fields share their names with the structs of the same module
+fn deduplicate_identifiers(tables: &mut [Table]) {
+    let mut table_order = (0..tables.len()).collect::<Vec<usize>>();
+    table_order.sort_by_key(|index| tables[*index].id);
 
-                        fn get_table_id(&self) -> usize {
-                            Self::get_static_table_id()
-                        }
+    let mut type_names = HashMap::<usize, Identifiers>::new();
+    for table_index in table_order.iter() {
+        let table = &mut tables[*table_index];
+        let identifiers = type_names.entry(table.database_id).or_insert_with(|| {
+            let mut identifiers = Identifiers::new(Case::Pascal);
+            identifiers.reserve(RESERVED_TYPE_NAMES);
+            identifiers
+        });
+        table.rename =
+            Some(identifiers.unique_with_suffixes(table.get_struct_name(), &["Write", "Patch"]));
+    }
 
-                        fn get_id(&self) -> Identifier {
-                            #primary_id_function
-                        }
+    for table_index in table_order {
+        let table = &mut tables[table_index];
+        let Some(identifiers) = type_names.get_mut(&table.database_id) else {
+            continue;
+        };
+        if let Some(fields) = table.fields.as_mut() {
+            let mut field_order = (0..fields.len()).collect::<Vec<usize>>();
+            field_order.sort_by_key(|index| fields[*index].get_id());
 
-                        fn get_table_id_field(&self) -> String {
-                            #primary_field_id.to_string()
-                        }
-                }});
+            let mut field_names = Identifiers::new(Snake);
+            for field_index in field_order {
+                let field = &mut fields[field_index];
+                let field_name = field_names.unique(field.get_name());
+                if field_name.ne(&field.get_name()) {
+                    let mut options = field.get_options().clone();
+                    options.rename = Some(field_name);
+                    field.set_options(options);
+                }
+                field.name_types(&table.name, identifiers);
+            }
+        }
+    }
+}
 
-                let testname = format_ident!("test_list_{}", table.get_struct_name().to_lowercase());
-                // Generate tests
-                let tests = quote! {
-                    #[tokio::test]
-                async fn #testname() {
-                    let client = Client::new(&std::env::var("BASEROW_TOKEN").unwrap(), None).unwrap();
+// Later overrides win if several of them match the same table or field
+fn apply_field_options(tables: &mut [Table], options: &FieldOptions, overrides: &[Override]) {
+    for table in tables {
+        let table_overrides = overrides
+            .iter()
+            .filter(|table_override| table_override.table.matches(table.id, &table.name))
+            .collect::<Vec<&Override>>();
 
-                    match client.list::<#struct_name>().await {
-                        Ok(result) => {
-                            println!("Got {} #struct_name", result.len());
-                            for schedule in &result {
-                                println!("{:?}", schedule);
-                            }
-                        },
-                        Err(e) => {assert!(false)},
-                    }}
-                            };
+        for table_override in table_overrides.iter().filter(|o| o.field.is_none()) {
+            if let Some(rename) = &table_override.rename {
+                table.rename = Some(rename.clone());
+            }
+        }
 
-                mod_file
-                    .write_all(
-                        prettyplease::unparse(&syn::parse_file(&structs.to_string()).unwrap())
-                            .as_bytes(),
-                    )
-                    .unwrap();
+        for field in table.fields.iter_mut().flatten() {
+            let mut field_options = options.clone();
+            for field_override in table_overrides.iter().filter(|o| {
+                o.field
+                    .as_ref()
+                    .is_some_and(|f| f.matches(field.get_id(), field.get_original_name()))
+            }) {
+                if let Some(rust_type) = &field_override.rust_type {
+                    field_options.rust_type = Some(rust_type.clone());
+                }
+                if let Some(with) = &field_override.with {
+                    field_options.serde_with = Some(with.clone());
+                }
+                if let Some(rename) = &field_override.rename {
+                    field_options.rename = Some(rename.clone());
+                }
             }
-            structs.extend(generate_deserializers());
+            field.set_options(field_options);
+        }
+    }
+}
+
+fn resolve_lookup_targets(tables: &mut [Table]) {
+    // Lookups can only target tables in the same database, but field ids are unique across all
+    // databases, so there is no need to separate by database here
+    let all_fields = tables
+        .iter()
+        .flat_map(|table| {
+            table
+                .fields
+                .iter()
+                .flatten()
+                .map(|field| (field.get_id(), (table.name.clone(), field.clone())))
+        })
+        .collect::<HashMap<usize, (String, TableField)>>();
+
+    for table in tables {
+        for field in table.fields.iter_mut().flatten() {
+            field.resolve_lookup_target(&all_fields);
+        }
+    }
+}
+
+// Lookups into select fields are typed with the enum of the target field, which is only
+// generated if neither the field nor its table are left out
+fn drop_missing_lookup_targets(tables: &mut [Table], databases: &[Database]) {
+    let generated_fields = tables
+        .iter()
+        .filter(|table| {
+            databases
+                .iter()
+                .any(|d| d.id.eq(&table.database_id) && d.includes_table(&table.name))
+        })
+        .flat_map(|table| table.fields.iter().flatten().map(|field| field.get_id()))
+        .collect::<HashSet<usize>>();
 
-            // Print formated code to stdout
-            let syntax_tree = syn::parse_file(&structs.to_string()).unwrap();
-            code_file
-                .write_all(prettyplease::unparse(&syntax_tree).as_bytes())
-                .unwrap();
-            code_file.flush().unwrap();
-            mod_file.flush().unwrap();
+    for table in tables {
+        for field in table.fields.iter_mut().flatten() {
+            field.drop_lookup_target(&generated_fields);
         }
     }
 }
@@ -262,8 +964,91 @@ fn get_primary_field(fields: Option<&Vec<TableField>>) -> &TableField {
     }
 }
 
+fn generate_shared_types() -> TokenStream {
+    quote! {
+        /// A Baserow user, as referenced by collaborator, created by and last modified by fields.
+        #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+        pub struct Collaborator {
+            pub id: usize,
+            pub name: String,
+        }
+
+        /// Value of a rating field, Baserow allows values from 0 up to the maximum configured
+        /// for the field, which is `MAX` here.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+        pub struct Rating<const MAX: u8>(u8);
+
+        impl<const MAX: u8> Rating<MAX> {
+            pub fn new(value: u8) -> Result<Self, RatingOutOfRange> {
+                if value > MAX {
+                    Err(RatingOutOfRange { value, max: MAX })
+                } else {
+                    Ok(Self(value))
+                }
+            }
+
+            pub fn value(&self) -> u8 {
+                self.0
+            }
+        }
+
+        impl<const MAX: u8> TryFrom<u8> for Rating<MAX> {
+            type Error = RatingOutOfRange;
+
+            fn try_from(value: u8) -> Result<Self, Self::Error> {
+                Self::new(value)
+            }
+        }
+
+        impl<const MAX: u8> Serialize for Rating<MAX> {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                serializer.serialize_u8(self.0)
+            }
+        }
+
+        impl<'de, const MAX: u8> Deserialize<'de> for Rating<MAX> {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                Self::new(u8::deserialize(deserializer)?).map_err(de::Error::custom)
+            }
+        }
+
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct RatingOutOfRange {
+            pub value: u8,
+            pub max: u8,
+        }
+
+        impl fmt::Display for RatingOutOfRange {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "rating {} is out of range 0..={}", self.value, self.max)
+            }
+        }
+
+        impl std::error::Error for RatingOutOfRange {}
+
+        /// A single value of a lookup field, `id` is the id of the row the value was looked up in.
+        #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+        pub struct LookupValue<T> {
+            pub id: usize,
+            #[serde(
+                deserialize_with = "lookup_value_or_null",
+                bound(deserialize = "T: de::DeserializeOwned")
+            )]
+            pub value: Option<T>,
+        }
+    }
+}
+
+// Generated for every database, the helpers are only used if a table has a field they apply to
 fn generate_deserializers() -> TokenStream {
     quote! {
+        #[allow(dead_code)]
         fn isize_or_null<'de, D>(deserializer: D) -> Result<Option<isize>, D::Error>
     where
         D: Deserializer<'de>,
@@ -281,7 +1066,21 @@ fn generate_deserializers() -> TokenStream {
             where
                 E: de::Error,
             {
-                Ok(Some(isize::from_str(value).unwrap()))
+                isize::from_str(value).map(Some).map_err(de::Error::custom)
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                isize::try_from(value).map(Some).map_err(de::Error::custom)
+            }
+
+            fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                isize::try_from(value).map(Some).map_err(de::Error::custom)
             }
 
             fn visit_unit<E>(self) -> Result<Self::Value, E>
@@ -296,6 +1095,7 @@ fn generate_deserializers() -> TokenStream {
     }
 
 
+    #[allow(dead_code)]
     fn usize_or_null<'de, D>(deserializer: D) -> Result<Option<usize>, D::Error>
     where
         D: Deserializer<'de>,
@@ -313,7 +1113,21 @@ fn generate_deserializers() -> TokenStream {
             where
                 E: de::Error,
             {
-                Ok(Some(usize::from_str(value).unwrap()))
+                usize::from_str(value).map(Some).map_err(de::Error::custom)
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                usize::try_from(value).map(Some).map_err(de::Error::custom)
+            }
+
+            fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                usize::try_from(value).map(Some).map_err(de::Error::custom)
             }
 
             fn visit_unit<E>(self) -> Result<Self::Value, E>
@@ -327,6 +1141,7 @@ fn generate_deserializers() -> TokenStream {
         deserializer.deserialize_any(UsizeOrNull)
     }
 
+    #[allow(dead_code)]
     fn float_or_null<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
     where
         D: Deserializer<'de>,
@@ -344,23 +1159,143 @@ fn generate_deserializers() -> TokenStream {
             where
                 E: de::Error,
             {
-                Ok(Some(f64::from_str(value).unwrap()))
+                f64::from_str(value).map(Some).map_err(de::Error::custom)
             }
 
-            fn visit_unit<E>(self) -> Result<Self::Value, E>
+            fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
             where
                 E: de::Error,
             {
-                Ok(None)
+                Ok(Some(value as f64))
             }
-        }
 
-        deserializer.deserialize_any(FloatOrNull)
-    }
-        }
-}
+            fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(Some(value as f64))
+            }
 
-fn generate_extra_structs(
+            fn visit_f64<E>(self, value: f64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(Some(value))
+            }
+
+            fn visit_unit<E>(self) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(None)
+            }
+        }
+
+        deserializer.deserialize_any(FloatOrNull)
+    }
+
+    #[allow(dead_code)]
+    fn date_or_null<'de, D>(deserializer: D) -> Result<Option<NaiveDate>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct DateOrNull;
+
+        impl<'de> Visitor<'de> for DateOrNull {
+            type Value = Option<NaiveDate>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("ISO date or null")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                // Dates are sent as YYYY-MM-DD, but be lenient in case we get a full timestamp
+                let date = value.get(..10).unwrap_or(value);
+                NaiveDate::from_str(date).map(Some).map_err(de::Error::custom)
+            }
+
+            fn visit_unit<E>(self) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(None)
+            }
+        }
+
+        deserializer.deserialize_any(DateOrNull)
+    }
+
+    #[allow(dead_code)]
+    fn datetime_or_null<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct DateTimeOrNull;
+
+        impl<'de> Visitor<'de> for DateTimeOrNull {
+            type Value = Option<DateTime<Utc>>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("ISO timestamp or null")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                DateTime::parse_from_rfc3339(value)
+                    .map(|timestamp| Some(timestamp.with_timezone(&Utc)))
+                    .map_err(de::Error::custom)
+            }
+
+            fn visit_unit<E>(self) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(None)
+            }
+        }
+
+        deserializer.deserialize_any(DateTimeOrNull)
+    }
+
+    #[allow(dead_code)]
+    fn patch_duration_as_seconds<S>(
+        value: &Option<Option<TimeDelta>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        duration_as_seconds(&value.flatten(), serializer)
+    }
+
+    #[allow(dead_code)]
+    fn lookup_value_or_null<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: de::DeserializeOwned,
+    {
+        match serde_json::Value::deserialize(deserializer)? {
+            serde_json::Value::Null => Ok(None),
+            // Numbers in lookups are sent as strings just like everywhere else, so try parsing
+            // the content if the string itself isn't what we are looking for
+            serde_json::Value::String(value) => serde_json::from_value(serde_json::Value::String(value.clone()))
+                .or_else(|_| serde_json::from_str(&value))
+                .map(Some)
+                .map_err(de::Error::custom),
+            value => serde_json::from_value(value)
+                .map(Some)
+                .map_err(de::Error::custom),
+        }
+    }
+        }
+}
+
+fn generate_extra_structs(
     fields: Option<&Vec<TableField>>,
     table_name: &str,
 ) -> Option<TokenStream> {
@@ -375,18 +1310,151 @@ fn generate_extra_structs(
     }
 }
 
+// A `FieldId` constant per field and `FIELDS` describing all of them, so code can refer to fields
+// without spelling out their ids
+fn generate_field_consts(fields: Option<&Vec<TableField>>) -> Option<TokenStream> {
+    if let Some(fields) = fields {
+        let mut consts_stream = TokenStream::new();
+        let mut metas_stream = TokenStream::new();
+        for field in fields {
+            let const_name = format_ident!("FIELD_{}", field.get_name().to_uppercase());
+            let field_id = Literal::usize_unsuffixed(field.get_id());
+            let name = field.get_original_name();
+            let field_type = field.get_baserow_type();
+            let read_only = field.is_read_only();
+            let primary = field.is_primary();
+            let options = field
+                .get_select_options()
+                .unwrap_or_default()
+                .iter()
+                .map(|option| &option.value);
+            let settings = field.get_type_settings();
+            let formula_type = option_tokens(settings.formula_type);
+            let array_formula_type = option_tokens(settings.array_formula_type);
+            let number_decimal_places = option_tokens(
+                settings
+                    .number_decimal_places
+                    .map(Literal::usize_unsuffixed),
+            );
+            let number_negative = option_tokens(settings.number_negative);
+            let date_include_time = option_tokens(settings.date_include_time);
+            let max_value = option_tokens(settings.max_value.map(Literal::usize_unsuffixed));
+            let doc = format!(" Id of the field `{}`.", name);
+            consts_stream.extend(quote! {
+                #[doc = #doc]
+                pub const #const_name: FieldId = FieldId::new(#field_id);
+            });
+            metas_stream.extend(quote! {
+                FieldMeta {
+                    name: #name,
+                    id: Self::#const_name,
+                    field_type: #field_type,
+                    read_only: #read_only,
+                    primary: #primary,
+                    options: &[#(#options),*],
+                    formula_type: #formula_type,
+                    array_formula_type: #array_formula_type,
+                    number_decimal_places: #number_decimal_places,
+                    number_negative: #number_negative,
+                    date_include_time: #date_include_time,
+                    max_value: #max_value,
+                },
+            });
+        }
+        Some(quote! {
+            #consts_stream
+
+            pub const FIELDS: &[FieldMeta] = &[
+                #metas_stream
+            ];
+        })
+    } else {
+        None
+    }
+}
+
+fn option_tokens<T: ToTokens>(value: Option<T>) -> TokenStream {
+    match value {
+        Some(value) => quote! { Some(#value) },
+        None => quote! { None },
+    }
+}
+
+fn generate_helper_fns(fields: Option<&Vec<TableField>>) -> Option<TokenStream> {
+    if let Some(fields) = fields {
+        let mut helper_fns_stream = TokenStream::new();
+        for field in fields {
+            helper_fns_stream.extend(field.get_helper_fns());
+        }
+        Some(helper_fns_stream)
+    } else {
+        None
+    }
+}
+
 fn generate_fields(fields: Option<&Vec<TableField>>, table_name: &str) -> Option<TokenStream> {
     if let Some(fields) = fields {
         let mut field_stream = TokenStream::new();
         for field in fields {
-            // Prepare some values that most branches of the following code will need
-            let field_name = format_ident!("{}", field.get_name().to_case(Case::Snake));
-            let field_type = format_ident!("{}", field.get_rust_type(table_name));
-            let field_id = format!("field_{}", field.get_id());
-            let deserializer = field.get_deserializer();
+            field_stream.extend(generate_field(field, table_name, false));
+        }
+        Some(field_stream)
+    } else {
+        None
+    }
+}
+
+// Write models only contain the fields Baserow accepts when creating or updating rows.
+// The primary field is always kept, since it is needed to find the row to update, but it is
+// not sent if Baserow computes it.
+fn generate_write_fields(
+    fields: Option<&Vec<TableField>>,
+    table_name: &str,
+) -> Option<TokenStream> {
+    if let Some(fields) = fields {
+        let mut field_stream = TokenStream::new();
+        for field in get_write_fields(fields) {
+            field_stream.extend(generate_field(field, table_name, field.is_read_only()));
+        }
+        Some(field_stream)
+    } else {
+        None
+    }
+}
+
+fn generate_write_conversion(fields: Option<&Vec<TableField>>) -> Option<TokenStream> {
+    if let Some(fields) = fields {
+        let mut conversion_stream = TokenStream::new();
+        for field in get_write_fields(fields) {
+            let field_name = format_ident!("{}", field.get_name());
+            conversion_stream.extend(quote! {
+                #field_name: value.#field_name,
+            });
+        }
+        Some(conversion_stream)
+    } else {
+        None
+    }
+}
+
+// Patches only contain the fields that should be changed, a field that is `None` is not sent,
+// `Some(None)` clears the field in Baserow.
+fn generate_patch_fields(
+    fields: Option<&Vec<TableField>>,
+    table_name: &str,
+) -> Option<TokenStream> {
+    if let Some(fields) = fields {
+        let mut field_stream = TokenStream::new();
+        for field in fields.iter().filter(|field| !field.is_read_only()) {
+            let field_name = format_ident!("{}", field.get_name());
+            let field_type = syn::parse_str::<syn::Type>(&field.get_rust_type(table_name)).unwrap();
+            let field_id = field.get_api_name();
+            let serializer = field.get_patch_serializer(table_name);
+            let doc = field.get_doc();
             field_stream.extend(quote! {
-                #[serde(rename = #field_id #deserializer)]
-                pub #field_name: Option<#field_type>,
+                #doc
+                #[serde(rename = #field_id, skip_serializing_if = "Option::is_none" #serializer)]
+                pub #field_name: Option<Option<#field_type>>,
             });
         }
         Some(field_stream)
@@ -395,6 +1463,138 @@ fn generate_fields(fields: Option<&Vec<TableField>>, table_name: &str) -> Option
     }
 }
 
+fn generate_patch_setters(
+    fields: Option<&Vec<TableField>>,
+    table_name: &str,
+) -> Option<TokenStream> {
+    if let Some(fields) = fields {
+        let mut setter_stream = TokenStream::new();
+        for field in fields.iter().filter(|field| !field.is_read_only()) {
+            let field_name = format_ident!("{}", field.get_name());
+            // Prefixed, so that fields like `Default` don't shadow the methods of traits
+            let setter_name = format_ident!("with_{}", field.get_name());
+            let field_type = syn::parse_str::<syn::Type>(&field.get_rust_type(table_name)).unwrap();
+            setter_stream.extend(quote! {
+                pub fn #setter_name(mut self, value: Option<#field_type>) -> Self {
+                    self.#field_name = Some(value);
+                    self
+                }
+            });
+        }
+        Some(setter_stream)
+    } else {
+        None
+    }
+}
+
+fn generate_diff(fields: Option<&Vec<TableField>>) -> Option<TokenStream> {
+    if let Some(fields) = fields {
+        let mut diff_stream = TokenStream::new();
+        for field in fields.iter().filter(|field| !field.is_read_only()) {
+            let field_name = format_ident!("{}", field.get_name());
+            diff_stream.extend(quote! {
+                if self.#field_name != original.#field_name {
+                    patch.#field_name = Some(self.#field_name.clone());
+                }
+            });
+        }
+        Some(diff_stream)
+    } else {
+        None
+    }
+}
+
+fn generate_changed_fields(fields: Option<&Vec<TableField>>) -> Option<TokenStream> {
+    if let Some(fields) = fields {
+        let mut changed_stream = TokenStream::new();
+        for field in fields.iter().filter(|field| !field.is_read_only()) {
+            let field_name_str = field.get_name();
+            let field_name = format_ident!("{}", field_name_str);
+            changed_stream.extend(quote! {
+                if self.#field_name != other.#field_name {
+                    changed.push(#field_name_str);
+                }
+            });
+        }
+        Some(changed_stream)
+    } else {
+        None
+    }
+}
+
+// Baserow doesn't expose when a row was last changed, unless the table has a last modified field.
+// A field without the time only changes once a day, so edits on the same day would go unnoticed
+fn generate_updated_on(fields: Option<&Vec<TableField>>) -> TokenStream {
+    let last_modified = fields.and_then(|fields| {
+        fields.iter().find(|field| {
+            matches!(
+                field,
+                TableField::LastModified {
+                    date_include_time: true,
+                    ..
+                }
+            )
+        })
+    });
+
+    match last_modified {
+        Some(field) => {
+            let field_name = format_ident!("{}", field.get_name());
+            quote! {
+                self.#field_name.as_ref().map(|updated_on| updated_on.to_string())
+            }
+        }
+        None => quote! { None },
+    }
+}
+
+// Checks the values of `fields` in an impl of `Validate`, patches hold their values in another
+// Option, which `patch` accounts for
+fn generate_validation<'a>(
+    fields: impl Iterator<Item = &'a TableField>,
+    table_name: &str,
+    patch: bool,
+) -> TokenStream {
+    let mut validation_stream = TokenStream::new();
+    for field in fields {
+        if let Some(checks) = field.get_validation(table_name) {
+            let field_name = format_ident!("{}", field.get_name());
+            let pattern = if patch {
+                quote! { Some(Some(value)) }
+            } else {
+                quote! { Some(value) }
+            };
+            validation_stream.extend(quote! {
+                if let #pattern = &self.#field_name {
+                    #checks
+                }
+            });
+        }
+    }
+    validation_stream
+}
+
+fn get_write_fields(fields: &[TableField]) -> impl Iterator<Item = &TableField> {
+    fields
+        .iter()
+        .filter(|field| !field.is_read_only() || field.is_primary())
+}
+
+fn generate_field(field: &TableField, table_name: &str, skip_serializing: bool) -> TokenStream {
+    // Prepare some values that most branches of the following code will need
+    let field_name = format_ident!("{}", field.get_name());
+    let field_type = syn::parse_str::<syn::Type>(&field.get_rust_type(table_name)).unwrap();
+    let field_id = field.get_api_name();
+    let deserializer = field.get_deserializer();
+    let skip_serializing = skip_serializing.then(|| quote! {, skip_serializing});
+    let doc = field.get_doc();
+    quote! {
+        #doc
+        #[serde(rename = #field_id #deserializer #skip_serializing)]
+        pub #field_name: Option<#field_type>,
+    }
+}
+
 fn generate_primary_id_fn(primary_field: &TableField, table_name: &str) -> TokenStream {
     let field_name = format_ident!("{}", primary_field.get_name());
 
@@ -417,3 +1617,530 @@ fn generate_primary_id_fn(primary_field: &TableField, table_name: &str) -> Token
         },
     }
 }
+
+// Only generated when exact decimals are enabled, so code that doesn't use them doesn't need
+// rust_decimal
+fn generate_decimal_helpers() -> TokenStream {
+    quote! {
+        #[allow(dead_code)]
+        fn decimal_or_null<'de, D>(deserializer: D) -> Result<Option<Decimal>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            struct DecimalOrNull;
+
+            impl<'de> Visitor<'de> for DecimalOrNull {
+                type Value = Option<Decimal>;
+
+                fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                    formatter.write_str("decimal number or null")
+                }
+
+                fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+                where
+                    E: de::Error,
+                {
+                    Decimal::from_str(value).map(Some).map_err(de::Error::custom)
+                }
+
+                fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+                where
+                    E: de::Error,
+                {
+                    Ok(Some(Decimal::from(value)))
+                }
+
+                fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E>
+                where
+                    E: de::Error,
+                {
+                    Ok(Some(Decimal::from(value)))
+                }
+
+                fn visit_f64<E>(self, value: f64) -> Result<Self::Value, E>
+                where
+                    E: de::Error,
+                {
+                    Decimal::try_from(value).map(Some).map_err(de::Error::custom)
+                }
+
+                fn visit_unit<E>(self) -> Result<Self::Value, E>
+                where
+                    E: de::Error,
+                {
+                    Ok(None)
+                }
+            }
+
+            deserializer.deserialize_any(DecimalOrNull)
+        }
+
+        #[allow(dead_code)]
+        fn decimal_as_string<const PLACES: u32, S>(
+            value: &Option<Decimal>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            match value {
+                Some(value) => serializer.serialize_str(&format!(
+                    "{:.*}",
+                    PLACES as usize,
+                    value.round_dp(PLACES)
+                )),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        #[allow(dead_code)]
+        fn patch_decimal_as_string<const PLACES: u32, S>(
+            value: &Option<Option<Decimal>>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            decimal_as_string::<PLACES, S>(&value.flatten(), serializer)
+        }
+    }
+}
+
+// Only generated when typed strings are enabled, so code that doesn't use them doesn't need
+// uuid
+fn generate_typed_string_helpers() -> TokenStream {
+    quote! {
+        #[allow(dead_code)]
+        fn parse_or_null<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+        where
+            D: Deserializer<'de>,
+            T: FromStr,
+            T::Err: fmt::Display,
+        {
+            // Baserow sends empty text fields as empty strings
+            match Option::<String>::deserialize(deserializer)? {
+                Some(value) if !value.is_empty() => {
+                    T::from_str(&value).map(Some).map_err(de::Error::custom)
+                }
+                _ => Ok(None),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::baserow_config::{Database, Override, Reference};
+    use crate::field_types::{FieldOptions, TableField};
+    use crate::generator::{
+        GENERATED_HEADER, Generator, Schema, Table, apply_field_options, deduplicate_identifiers,
+        generate_field, generate_field_consts, generate_patch_fields, generate_patch_setters,
+        generate_table, generate_updated_on, generate_validation, get_write_fields, parse_fields,
+        remove_database_files,
+    };
+    use std::fs;
+
+    fn load_fields(file: &str) -> Vec<TableField> {
+        let contents = fs::read_to_string(file).expect("Should have been able to read the file");
+        serde_json::from_str(&contents).expect("file should be proper JSON")
+    }
+
+    #[test]
+    fn test_write_fields() {
+        let fields = load_fields("testdata/field_types1.json");
+
+        let write_fields = get_write_fields(&fields)
+            .map(|field| field.get_original_name().as_str())
+            .collect::<Vec<&str>>();
+        assert!(write_fields.contains(&"Name"));
+        assert!(write_fields.contains(&"Budget"));
+        assert!(!write_fields.contains(&"Total"));
+        assert!(!write_fields.contains(&"Customer revenue"));
+        assert!(!write_fields.contains(&"Created on"));
+    }
+
+    #[test]
+    fn test_patch_fields() {
+        let fields = load_fields("testdata/field_types1.json");
+
+        let patch_fields = generate_patch_fields(Some(&fields), "Projects")
+            .unwrap()
+            .to_string();
+        assert!(patch_fields.contains("pub budget : Option < Option < f64 >>"));
+        assert!(!patch_fields.contains("pub total"));
+
+        let setters = generate_patch_setters(Some(&fields), "Projects")
+            .unwrap()
+            .to_string();
+        assert!(setters.contains("pub fn with_budget (mut self , value : Option < f64 >) -> Self"));
+    }
+
+    #[test]
+    fn test_user_field_names() {
+        let mut fields = load_fields("testdata/field_types1.json");
+        let budget = fields
+            .iter()
+            .find(|field| field.get_original_name() == "Budget")
+            .unwrap();
+        let field_id = format!("rename = \"field_{}\"", budget.get_id());
+        assert!(
+            generate_field(budget, "Projects", false)
+                .to_string()
+                .contains(&field_id)
+        );
+
+        for field in fields.iter_mut() {
+            field.set_options(FieldOptions {
+                user_field_names: true,
+                ..FieldOptions::default()
+            });
+        }
+        let patch_fields = generate_patch_fields(Some(&fields), "Projects")
+            .unwrap()
+            .to_string();
+        assert!(patch_fields.contains("rename = \"Budget\""));
+        assert!(!patch_fields.contains(&field_id));
+
+        let table = Table {
+            id: 101,
+            name: "Projects".to_string(),
+            order: 1,
+            database_id: 10,
+            fields: Some(fields),
+            rename: None,
+        };
+        let code = generate_table(&table).to_string();
+        assert_eq!(
+            code.matches("const USER_FIELD_NAMES : bool = true").count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_validation() {
+        let fields = load_fields("testdata/field_types1.json");
+
+        let validation = generate_validation(fields.iter(), "Projects", false).to_string();
+        assert!(validation.contains("is_valid_url (value)"));
+        assert!(validation.contains("exceeds_decimal_places (* value , 2u32)"));
+
+        let patch_validation = generate_validation(fields.iter(), "Projects", true).to_string();
+        assert!(patch_validation.contains("if let Some (Some (value)) = & self . website"));
+    }
+
+    #[test]
+    fn test_overrides() {
+        let mut tables = vec![Table {
+            id: 101,
+            name: "Projects".to_string(),
+            order: 1,
+            database_id: 10,
+            fields: Some(load_fields("testdata/field_types1.json")),
+            rename: None,
+        }];
+        let overrides = vec![
+            Override {
+                table: Reference::Name("Projects".to_string()),
+                field: None,
+                rust_type: None,
+                with: None,
+                rename: Some("Project".to_string()),
+            },
+            Override {
+                table: Reference::Id(101),
+                field: Some(Reference::Name("Notes".to_string())),
+                rust_type: Some("serde_json::Value".to_string()),
+                with: Some("json_string".to_string()),
+                rename: Some("notes_json".to_string()),
+            },
+        ];
+        apply_field_options(&mut tables, &FieldOptions::default(), &overrides);
+
+        let table = &tables[0];
+        assert_eq!(table.get_struct_name(), "Project");
+        let notes = table
+            .fields
+            .iter()
+            .flatten()
+            .find(|field| field.get_original_name().eq("Notes"))
+            .unwrap();
+        assert_eq!(notes.get_name(), "notes_json");
+        assert_eq!(notes.get_rust_type("Projects"), "serde_json::Value");
+
+        let patch_fields = generate_patch_fields(table.fields.as_ref(), "Projects")
+            .unwrap()
+            .to_string();
+        assert!(patch_fields.contains("serialize_with = \"patch_projects_notes_json\""));
+    }
+
+    #[test]
+    fn test_skip_unknown_fields() {
+        let contents = fs::read_to_string("testdata/field_types2.json").unwrap();
+        let mut fields = serde_json::from_str::<Vec<serde_json::Value>>(&contents).unwrap();
+        let known = fields.len();
+        fields.push(serde_json::json!({"id": 2099, "name": "Mystery", "type": "hologram"}));
+
+        assert_eq!(parse_fields(102, fields).len(), known);
+    }
+
+    #[test]
+    fn test_field_consts() {
+        let fields = load_fields("testdata/field_types1.json");
+
+        let consts = generate_field_consts(Some(&fields)).unwrap().to_string();
+        assert!(consts.contains("pub const FIELD_DEADLINE : FieldId = FieldId :: new (1010)"));
+        assert!(consts.contains("name : \"Contact Email\" , id : Self :: FIELD_CONTACT_EMAIL"));
+        assert!(consts.contains("options : & [\"In progress\" , \"Done\"]"));
+        assert!(
+            consts.contains("number_decimal_places : Some (2) , number_negative : Some (false)")
+        );
+    }
+
+    #[test]
+    fn test_updated_on() {
+        let mut fields = load_fields("testdata/field_types1.json");
+        assert!(
+            generate_updated_on(Some(&fields))
+                .to_string()
+                .contains("self . last_modified")
+        );
+
+        for field in fields.iter_mut() {
+            if let TableField::LastModified {
+                date_include_time, ..
+            } = field
+            {
+                *date_include_time = false;
+            }
+        }
+        assert_eq!(generate_updated_on(Some(&fields)).to_string(), "None");
+    }
+
+    #[test]
+    fn test_file_per_table() {
+        let table = Table {
+            id: 101,
+            name: "Projects".to_string(),
+            order: 1,
+            database_id: 10,
+            fields: Some(load_fields("testdata/field_types1.json")),
+            rename: None,
+        };
+
+        let files = Generator::new("token")
+            .unwrap()
+            .generate_database_files("crm", &[&table]);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].0.to_str(), Some("crm.rs"));
+        assert!(files[0].1.to_string().contains("pub struct Projects"));
+
+        let files = Generator::new("token")
+            .unwrap()
+            .with_file_per_table(true)
+            .generate_database_files("crm", &[&table]);
+        let paths = files
+            .iter()
+            .map(|(path, _)| path.to_str().unwrap())
+            .collect::<Vec<&str>>();
+        assert_eq!(paths, vec!["crm/mod.rs", "crm/projects.rs"]);
+        let module = files[0].1.to_string();
+        assert!(module.contains("mod projects ; pub use projects :: * ;"));
+        assert!(!module.contains("pub struct Projects"));
+        assert!(files[1].1.to_string().starts_with("use super :: * ;"));
+    }
+
+    #[test]
+    fn test_lookup_into_excluded_table() {
+        let contents = fs::read_to_string("testdata/field_types1.json").unwrap();
+        let mut fields = serde_json::from_str::<Vec<serde_json::Value>>(&contents).unwrap();
+        // Customers gets its own copy of the primary field and a lookup of the project status
+        fields.retain(|field| field["name"] == "Name" || field["name"] == "Customer city");
+        for field in fields.iter_mut() {
+            field["id"] = (field["id"].as_u64().unwrap() + 1000).into();
+            field["table_id"] = 102.into();
+        }
+        fields[1]["target_field_id"] = 1019.into();
+        let table = |id: usize, name: &str, fields: Vec<TableField>| Table {
+            id,
+            name: name.to_string(),
+            order: 1,
+            database_id: 10,
+            fields: Some(fields),
+            rename: None,
+        };
+        let schema = Schema {
+            databases: vec![
+                serde_json::from_value::<Database>(serde_json::json!({
+                    "name": "CRM",
+                    "id": 10,
+                    "exclude_tables": ["Projects"]
+                }))
+                .unwrap(),
+            ],
+            tables: vec![
+                table(101, "Projects", load_fields("testdata/field_types1.json")),
+                table(102, "Customers", parse_fields(102, fields)),
+            ],
+        };
+
+        let out_dir =
+            std::env::temp_dir().join(format!("baserow-codegen-lookup-{}", std::process::id()));
+        Generator::from_schema(schema)
+            .generate_to(&out_dir)
+            .unwrap();
+        let code = fs::read_to_string(out_dir.join("crm.rs")).unwrap();
+        fs::remove_dir_all(&out_dir).unwrap();
+        assert!(!code.contains("ProjectsStatus"));
+        assert!(code.contains("Option<Vec<LookupValue<serde_json::Value>>>"));
+    }
+
+    #[test]
+    fn test_colliding_type_names() {
+        let contents = fs::read_to_string("testdata/field_types1.json").unwrap();
+        let mut fields = serde_json::from_str::<Vec<serde_json::Value>>(&contents).unwrap();
+        fields.retain(|field| field["name"] == "Name");
+        fields[0]["id"] = 3001.into();
+        fields[0]["table_id"] = 103.into();
+        let mut tables = vec![
+            Table {
+                id: 101,
+                name: "Projects".to_string(),
+                order: 1,
+                database_id: 10,
+                fields: Some(load_fields("testdata/field_types1.json")),
+                rename: None,
+            },
+            Table {
+                id: 103,
+                name: "Projects Status".to_string(),
+                order: 2,
+                database_id: 10,
+                fields: Some(parse_fields(103, fields)),
+                rename: None,
+            },
+        ];
+        deduplicate_identifiers(&mut tables);
+
+        assert_eq!(tables[1].get_struct_name(), "ProjectsStatus");
+        let status = tables[0]
+            .fields
+            .iter()
+            .flatten()
+            .find(|field| field.get_original_name() == "Status")
+            .unwrap();
+        assert_eq!(status.get_rust_type("Projects"), "ProjectsStatus2");
+    }
+
+    #[test]
+    fn test_generate_selected_databases() {
+        let schema = Schema {
+            databases: vec![
+                serde_json::from_value::<Database>(serde_json::json!({"name": "CRM", "id": 10}))
+                    .unwrap(),
+                serde_json::from_value::<Database>(serde_json::json!({"name": "HR", "id": 11}))
+                    .unwrap(),
+            ],
+            tables: vec![Table {
+                id: 101,
+                name: "Projects".to_string(),
+                order: 1,
+                database_id: 10,
+                fields: Some(load_fields("testdata/field_types1.json")),
+                rename: None,
+            }],
+        };
+        let out_dir =
+            std::env::temp_dir().join(format!("baserow-codegen-selected-{}", std::process::id()));
+        fs::create_dir_all(&out_dir).unwrap();
+        fs::write(out_dir.join("hr.rs"), "// generated earlier\n").unwrap();
+
+        Generator::from_schema(schema)
+            .with_selected_databases(vec![10])
+            .generate_to(&out_dir)
+            .unwrap();
+        let module = fs::read_to_string(out_dir.join("mod.rs")).unwrap();
+        let crm = fs::read_to_string(out_dir.join("crm.rs")).unwrap();
+        let hr = fs::read_to_string(out_dir.join("hr.rs")).unwrap();
+        fs::remove_dir_all(&out_dir).unwrap();
+        assert_eq!(
+            module,
+            format!("{}pub mod crm;\npub mod hr;\n", GENERATED_HEADER)
+        );
+        assert!(crm.contains("pub struct Projects {"));
+        assert_eq!(hr, "// generated earlier\n");
+    }
+
+    #[test]
+    fn test_remove_database_files() {
+        let out_dir =
+            std::env::temp_dir().join(format!("baserow-codegen-remove-{}", std::process::id()));
+        fs::create_dir_all(out_dir.join("crm")).unwrap();
+        fs::create_dir_all(out_dir.join("models")).unwrap();
+        let generated = format!("{}pub struct Projects {{}}\n", GENERATED_HEADER);
+        fs::write(out_dir.join("crm.rs"), &generated).unwrap();
+        fs::write(out_dir.join("crm").join("projects.rs"), &generated).unwrap();
+        fs::write(out_dir.join("models.rs"), "pub mod helpers;\n").unwrap();
+        fs::write(
+            out_dir.join("models").join("helpers.rs"),
+            "pub fn help() {}\n",
+        )
+        .unwrap();
+        fs::write(out_dir.join("models").join("old.rs"), &generated).unwrap();
+
+        remove_database_files(&out_dir, "crm").unwrap();
+        remove_database_files(&out_dir, "models").unwrap();
+        let crm_removed = !out_dir.join("crm.rs").exists() && !out_dir.join("crm").exists();
+        let models_kept = out_dir.join("models.rs").exists()
+            && out_dir.join("models").join("helpers.rs").exists()
+            && !out_dir.join("models").join("old.rs").exists();
+        fs::remove_dir_all(&out_dir).unwrap();
+        assert!(crm_removed);
+        assert!(models_kept);
+    }
+
+    #[test]
+    fn test_generate_from_schema() {
+        let schema = Schema {
+            databases: vec![
+                serde_json::from_value::<Database>(serde_json::json!({"name": "CRM", "id": 10}))
+                    .unwrap(),
+            ],
+            tables: vec![Table {
+                id: 101,
+                name: "Projects".to_string(),
+                order: 1,
+                database_id: 10,
+                fields: Some(load_fields("testdata/field_types1.json")),
+                rename: None,
+            }],
+        };
+        let schema_file = std::env::temp_dir().join(format!(
+            "baserow-codegen-schema-{}.json",
+            std::process::id()
+        ));
+        fs::write(&schema_file, schema.to_json().unwrap()).unwrap();
+        let schema = Schema::from_file(&schema_file).unwrap();
+        fs::remove_file(&schema_file).unwrap();
+        assert_eq!(schema.get_tables(10), vec![(101, "Projects")]);
+
+        let out_dir =
+            std::env::temp_dir().join(format!("baserow-codegen-out-{}", std::process::id()));
+        Generator::from_schema(schema)
+            .generate_to(&out_dir)
+            .unwrap();
+        let module = fs::read_to_string(out_dir.join("mod.rs")).unwrap();
+        let code = fs::read_to_string(out_dir.join("crm.rs")).unwrap();
+        fs::remove_dir_all(&out_dir).unwrap();
+        assert_eq!(module, format!("{}pub mod crm;\n", GENERATED_HEADER));
+        assert!(code.contains("pub struct Projects {"));
+        assert!(code.contains("#[allow(unused_imports)]\nuse baserow_client::file::BaserowFile;"));
+
+        assert!(
+            Generator::new("token")
+                .unwrap()
+                .generate_to(&out_dir)
+                .is_err()
+        );
+        assert!(Generator::new("line\nbreak").is_err());
+    }
+}
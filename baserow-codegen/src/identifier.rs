@@ -0,0 +1,153 @@
+use convert_case::{Case, Casing};
+use deunicode::deunicode;
+use std::collections::HashSet;
+
+// Keywords of the 2024 edition, all of these need to be escaped when names collide with them
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Turn a name from Baserow into a valid Rust identifier in `case`, which is expected to be
+/// either `Case::Snake` or `Case::Pascal`.
+///
+/// Non ASCII characters are transliterated (`Größe` becomes `grosse`, emojis their name),
+/// anything else that can't be part of an identifier separates words.
+pub fn to_identifier(name: &str, case: Case) -> String {
+    let words = deunicode(name)
+        .replace('&', " and ")
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { ' ' })
+        .collect::<String>();
+    let mut identifier = words.to_case(case);
+
+    if identifier.is_empty() {
+        identifier = "unnamed".to_case(case);
+    }
+    if identifier.starts_with(|c: char| c.is_ascii_digit()) {
+        identifier = match case {
+            Case::Snake => format!("a_{}", identifier),
+            _ => format!("A{}", identifier),
+        };
+    }
+
+    if identifier.eq("type") {
+        // Kept for compatibility with code generated by earlier versions
+        "ty".to_string()
+    } else if KEYWORDS.contains(&identifier.as_str()) {
+        match case {
+            Case::Snake => format!("{}_", identifier),
+            _ => format!("{}Value", identifier),
+        }
+    } else {
+        identifier
+    }
+}
+
+/// Identifiers that were already handed out in a scope, like the fields of a struct or the
+/// variants of an enum.
+pub struct Identifiers {
+    case: Case<'static>,
+    used: HashSet<String>,
+}
+
+impl Identifiers {
+    pub fn new(case: Case<'static>) -> Self {
+        Self {
+            case,
+            used: HashSet::new(),
+        }
+    }
+
+    /// Mark identifiers as taken, without handing them out.
+    pub fn reserve(&mut self, identifiers: &[&str]) {
+        self.used
+            .extend(identifiers.iter().map(|identifier| identifier.to_string()));
+    }
+
+    /// Returns `identifier` the first time it is requested and numbers it from 2 on after that,
+    /// so results are deterministic as long as identifiers are requested in the same order.
+    pub fn unique(&mut self, identifier: String) -> String {
+        self.unique_with_suffixes(identifier, &[])
+    }
+
+    /// Like `unique`, but also takes the identifiers formed by appending each of `suffixes`,
+    /// `identifier` is numbered until all of them are free.
+    pub fn unique_with_suffixes(&mut self, identifier: String, suffixes: &[&str]) -> String {
+        let mut candidate = identifier.clone();
+        let mut number = 2;
+        while self.is_used(&candidate)
+            || suffixes
+                .iter()
+                .any(|suffix| self.is_used(&format!("{}{}", candidate, suffix)))
+        {
+            candidate = match self.case {
+                Case::Snake => format!("{}_{}", identifier, number),
+                _ => format!("{}{}", identifier, number),
+            };
+            number += 1;
+        }
+
+        for suffix in suffixes {
+            self.used.insert(format!("{}{}", candidate, suffix));
+        }
+        self.used.insert(candidate.clone());
+        candidate
+    }
+
+    fn is_used(&self, identifier: &str) -> bool {
+        self.used.contains(identifier)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::identifier::{Identifiers, to_identifier};
+    use convert_case::Case;
+
+    #[test]
+    fn test_to_identifier() {
+        assert_eq!(to_identifier("Größe (cm)", Case::Snake), "grosse_cm");
+        assert_eq!(
+            to_identifier("Sales & Marketing", Case::Pascal),
+            "SalesAndMarketing"
+        );
+        assert_eq!(to_identifier("e-mail", Case::Snake), "e_mail");
+        assert_eq!(to_identifier("1st place", Case::Snake), "a_1_st_place");
+        assert_eq!(to_identifier("🚀", Case::Snake), "rocket");
+        assert_eq!(to_identifier("!!!", Case::Pascal), "Unnamed");
+
+        assert_eq!(to_identifier("Type", Case::Snake), "ty");
+        assert_eq!(to_identifier("match", Case::Snake), "match_");
+        assert_eq!(to_identifier("self", Case::Pascal), "SelfValue");
+    }
+
+    #[test]
+    fn test_unique() {
+        let mut identifiers = Identifiers::new(Case::Snake);
+        assert_eq!(identifiers.unique("status".to_string()), "status");
+        assert_eq!(identifiers.unique("status".to_string()), "status_2");
+        assert_eq!(identifiers.unique("status".to_string()), "status_3");
+
+        let mut identifiers = Identifiers::new(Case::Pascal);
+        identifiers.reserve(&["Duration"]);
+        assert_eq!(identifiers.unique("Duration".to_string()), "Duration2");
+
+        let mut identifiers = Identifiers::new(Case::Pascal);
+        assert_eq!(
+            identifiers.unique("ProjectsWrite".to_string()),
+            "ProjectsWrite"
+        );
+        assert_eq!(
+            identifiers.unique_with_suffixes("Projects".to_string(), &["Write", "Patch"]),
+            "Projects2"
+        );
+        assert_eq!(
+            identifiers.unique("Projects2Patch".to_string()),
+            "Projects2Patch2"
+        );
+    }
+}
@@ -0,0 +1,4 @@
+pub mod baserow_config;
+mod field_types;
+pub mod generator;
+mod identifier;
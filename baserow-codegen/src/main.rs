@@ -1,26 +1,97 @@
-use std::{fs, io};
-use std::path::Path;
-use crate::baserow_config::BaserowConfig;
-use snafu::{ResultExt, Snafu};
+use baserow_codegen::baserow_config::{self, BaserowConfig, Database, Reference};
+use baserow_codegen::generator::{self, Generator};
+use clap::{Args, Parser, Subcommand};
+use snafu::{ResultExt, Snafu, ensure};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
 use std::process::exit;
-use crate::generator::Generator;
-
-mod baserow_config;
-mod field_types;
-mod generator;
+use std::{env, fs, io};
 
 #[derive(Snafu, Debug)]
 pub enum Error {
     #[snafu(display("Error obtaining configuration:\n {source}"))]
     Config { source: baserow_config::Error },
-    #[snafu(display("Error creating target directory [{path}]: {source}"))]
-    CreateTargetDir { source: io::Error, path: String },
-    
+    #[snafu(display(
+        "No token configured, set it in the configuration or in the environment variable [{token_env}]"
+    ))]
+    NoToken { token_env: String },
+    #[snafu(display("Database [{database}] is not part of the configuration"))]
+    UnknownDatabase { database: String },
+    #[snafu(display("Error generating code:\n {source}"))]
+    Generate { source: generator::Error },
+    #[snafu(display("Could not write schema to [{path}]: {source}"))]
+    WriteSchema { source: io::Error, path: String },
+    #[snafu(display("Could not read generated code in [{path}]: {source}"))]
+    ReadGenerated { source: io::Error, path: String },
+    #[snafu(display(
+        "Generated code in [{target}] is out of date, regenerate it to update: {}",
+        files.join(", ")
+    ))]
+    OutOfDate { target: String, files: Vec<String> },
+}
+
+/// Generate Rust structs for the tables of Baserow databases.
+#[derive(Parser, Debug)]
+#[command(name = "cargo-baserow", bin_name = "cargo baserow", version)]
+struct Cli {
+    #[command(flatten)]
+    options: Options,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Args, Debug)]
+struct Options {
+    /// Configuration file
+    #[arg(
+        long,
+        global = true,
+        env = "BASEROW_CONFIG",
+        default_value = "baserow_config.json"
+    )]
+    config: PathBuf,
+    /// Directory to write the code to, or file to write the schema to, instead of the
+    /// target_directory of the configuration
+    #[arg(long, global = true, env = "BASEROW_OUT")]
+    out: Option<PathBuf>,
+    /// Environment variable to read the token from, the token of the configuration is used if
+    /// it isn't set
+    #[arg(
+        long,
+        global = true,
+        env = "BASEROW_TOKEN_ENV",
+        default_value = "BASEROW_TOKEN"
+    )]
+    token_env: String,
+    /// Url of a self hosted Baserow instance
+    #[arg(long, global = true, env = "BASEROW_BASE_URL")]
+    base_url: Option<String>,
+    /// Only use the configured database with this name or id, can be repeated
+    #[arg(
+        long = "database",
+        global = true,
+        env = "BASEROW_DATABASE",
+        value_delimiter = ','
+    )]
+    databases: Vec<String>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Generate code for the configured databases
+    Generate,
+    /// Write the tables and fields of the configured databases as JSON, which can be used to
+    /// generate code from a build.rs
+    DumpSchema,
+    /// Fail if regenerating would change the generated code
+    Check,
+    /// List the tables of the configured databases
+    ListTables,
 }
 
 #[tokio::main]
 async fn main() {
-    match run().await {
+    match run(parse_args(env::args_os().collect())).await {
         Ok(_) => exit(0),
         Err(e) => {
             eprint!("{}", e);
@@ -29,14 +100,203 @@ async fn main() {
     };
 }
 
-async fn run() -> Result<(), Error> {
-    let config = BaserowConfig::new().context(ConfigSnafu)?;
-    let client = Generator::new(&config.token);
+// When run as `cargo baserow`, cargo passes the name of the subcommand on as first argument
+fn parse_args(mut args: Vec<OsString>) -> Cli {
+    if args.get(1).is_some_and(|arg| arg == "baserow") {
+        args.remove(1);
+    }
+    Cli::parse_from(args)
+}
+
+async fn run(cli: Cli) -> Result<(), Error> {
+    let options = cli.options;
+    let mut config = BaserowConfig::from_file(&options.config).context(ConfigSnafu)?;
+    if let Ok(token) = env::var(&options.token_env) {
+        config.token = token;
+    }
+    ensure!(
+        !config.token.is_empty(),
+        NoTokenSnafu {
+            token_env: &options.token_env
+        }
+    );
+    let databases = select_databases(&config.databases, &options.databases)?;
+
+    let mut generator = Generator::new(&config.token)
+        .context(GenerateSnafu)?
+        .with_exact_decimals(config.exact_decimals)
+        .with_typed_strings(config.typed_strings)
+        .with_user_field_names(config.user_field_names)
+        .with_file_per_table(config.file_per_table)
+        .with_overrides(config.overrides);
+    if let Some(base_url) = options.base_url.as_ref().or(config.base_url.as_ref()) {
+        generator = generator.with_base_url(base_url);
+    }
+    // The other databases keep their code, and their entries in mod.rs
+    if !options.databases.is_empty() {
+        generator = generator
+            .with_selected_databases(databases.iter().map(|database| database.id).collect());
+    }
+    let target_path = options
+        .out
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(&config.target_directory));
+
+    match cli.command {
+        Command::Generate => generator
+            .generate_structs(&config.databases, &target_path)
+            .await
+            .context(GenerateSnafu),
+        Command::DumpSchema => {
+            let schema = generator
+                .fetch_schema(&databases)
+                .await
+                .context(GenerateSnafu)?;
+            let json = schema.to_json().context(GenerateSnafu)?;
+            // Without an explicit file the schema goes to stdout, the target directory is meant
+            // for generated code
+            match options.out {
+                Some(_) => fs::write(&target_path, json).context(WriteSchemaSnafu {
+                    path: target_path.display().to_string(),
+                }),
+                None => {
+                    println!("{}", json);
+                    Ok(())
+                }
+            }
+        }
+        Command::Check => check(&generator, &config.databases, &target_path).await,
+        Command::ListTables => {
+            let schema = generator
+                .fetch_schema(&databases)
+                .await
+                .context(GenerateSnafu)?;
+            for database in schema.get_databases() {
+                println!("{} ({})", database.name, database.id);
+                for (id, name) in schema.get_tables(database.id) {
+                    match database.includes_table(name) {
+                        true => println!("  {} {}", id, name),
+                        false => println!("  {} {} (excluded)", id, name),
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+fn select_databases(databases: &[Database], selected: &[String]) -> Result<Vec<Database>, Error> {
+    if selected.is_empty() {
+        return Ok(databases.to_vec());
+    }
+
+    let references = selected
+        .iter()
+        .map(|database| match database.parse::<usize>() {
+            Ok(id) => Reference::Id(id),
+            Err(_) => Reference::Name(database.clone()),
+        })
+        .collect::<Vec<Reference>>();
+    for (reference, database) in references.iter().zip(selected) {
+        ensure!(
+            databases.iter().any(|d| reference.matches(d.id, &d.name)),
+            UnknownDatabaseSnafu { database }
+        );
+    }
+    Ok(databases
+        .iter()
+        .filter(|d| references.iter().any(|r| r.matches(d.id, &d.name)))
+        .cloned()
+        .collect())
+}
 
-    fs::create_dir_all(&config.target_directory).context(CreateTargetDirSnafu {path: &config.target_directory.to_string()})?;
-    let target_path = Path::new(&config.target_directory);
-    
-        client.generate_structs(&config.databases, target_path).await;
+// Generates into a temporary directory and compares every file with the one in the target
+// directory, files that only exist in the target directory are left for `generate` to clean up
+async fn check(
+    generator: &Generator,
+    databases: &[Database],
+    target_path: &Path,
+) -> Result<(), Error> {
+    let check_path = env::temp_dir().join(format!("baserow-codegen-check-{}", std::process::id()));
+    let result = generator
+        .generate_structs(databases, &check_path)
+        .await
+        .context(GenerateSnafu)
+        .and_then(|_| changed_files(&check_path, target_path, Path::new("")));
+    // Cleaning up is best effort, the check result is what matters
+    let _ = fs::remove_dir_all(&check_path);
+
+    let files = result?;
+    ensure!(
+        files.is_empty(),
+        OutOfDateSnafu {
+            target: target_path.display().to_string(),
+            files
+        }
+    );
+    println!(
+        "Generated code in [{}] is up to date",
+        target_path.display()
+    );
     Ok(())
 }
 
+// Paths of the files below `relative` in `generated` that are missing or different in `target`
+fn changed_files(generated: &Path, target: &Path, relative: &Path) -> Result<Vec<String>, Error> {
+    let directory = generated.join(relative);
+    let entries = fs::read_dir(&directory).context(ReadGeneratedSnafu {
+        path: directory.display().to_string(),
+    })?;
+
+    let mut changed = Vec::new();
+    for entry in entries {
+        let entry = entry.context(ReadGeneratedSnafu {
+            path: directory.display().to_string(),
+        })?;
+        let path = relative.join(entry.file_name());
+        if entry.path().is_dir() {
+            changed.extend(changed_files(generated, target, &path)?);
+            continue;
+        }
+        let expected = fs::read(entry.path()).context(ReadGeneratedSnafu {
+            path: entry.path().display().to_string(),
+        })?;
+        if fs::read(target.join(&path)).ok() != Some(expected) {
+            changed.push(path.display().to_string());
+        }
+    }
+    changed.sort();
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Command, parse_args};
+
+    #[test]
+    fn test_parse_args() {
+        let cli = parse_args(
+            [
+                "cargo-baserow",
+                "baserow",
+                "generate",
+                "--database",
+                "CRM,12",
+            ]
+            .iter()
+            .map(|arg| arg.into())
+            .collect(),
+        );
+        assert!(matches!(cli.command, Command::Generate));
+        assert_eq!(cli.options.databases, vec!["CRM", "12"]);
+
+        let cli = parse_args(
+            ["baserow-codegen", "list-tables", "--token-env", "CRM_TOKEN"]
+                .iter()
+                .map(|arg| arg.into())
+                .collect(),
+        );
+        assert!(matches!(cli.command, Command::ListTables));
+        assert_eq!(cli.options.token_env, "CRM_TOKEN");
+    }
+}
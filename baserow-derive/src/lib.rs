@@ -0,0 +1,301 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    Data, DeriveInput, Error, Field, Fields, GenericArgument, Ident, LitInt, PathArguments, Type,
+    parse_macro_input,
+};
+
+/// Implements `BaserowObject` for a struct written by hand, the same way `baserow-codegen`
+/// does for the structs it generates.
+///
+/// The table is set with `#[baserow(table = 123)]` on the struct and the field that identifies
+/// rows with `#[baserow(field = 456, primary)]`. All fields with a `field` id get a
+/// `FIELD_<NAME>` constant next to `TABLE_ID`, like generated structs have.
+///
+/// The struct is also used to write rows: `BaserowWriteObject` is implemented with the struct
+/// itself as `Read`, which needs `Serialize`, along with a `Validate` that finds no violations.
+/// `#[baserow(custom_validation)]` leaves `Validate` to be implemented by hand,
+/// `#[baserow(read_only)]` skips both for structs that are only read.
+///
+/// ```ignore
+/// #[derive(Serialize, Deserialize, BaserowObject)]
+/// #[baserow(table = 123)]
+/// struct Project {
+///     #[baserow(field = 456, primary)]
+///     #[serde(rename = "field_456")]
+///     name: Option<String>,
+///     #[baserow(field = 457)]
+///     #[serde(rename = "field_457")]
+///     budget: Option<f64>,
+/// }
+/// ```
+#[proc_macro_derive(BaserowObject, attributes(baserow))]
+pub fn derive_baserow_object(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+struct BaserowField<'a> {
+    ident: &'a Ident,
+    ty: &'a Type,
+    id: usize,
+    primary: bool,
+}
+
+struct BaserowStruct {
+    table_id: usize,
+    read_only: bool,
+    custom_validation: bool,
+}
+
+fn expand(input: &DeriveInput) -> Result<TokenStream2, Error> {
+    let BaserowStruct {
+        table_id,
+        read_only,
+        custom_validation,
+    } = parse_struct(input)?;
+    let named_fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "BaserowObject can only be derived for structs with named fields",
+                ));
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "BaserowObject can only be derived for structs",
+            ));
+        }
+    };
+
+    let mut fields = Vec::new();
+    for field in named_fields {
+        if let Some(field) = parse_field(field)? {
+            fields.push(field);
+        }
+    }
+    let primary_fields = fields
+        .iter()
+        .filter(|field| field.primary)
+        .collect::<Vec<&BaserowField>>();
+    let [primary_field] = primary_fields.as_slice() else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "exactly one field needs to be marked with `#[baserow(field = ..., primary)]`",
+        ));
+    };
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let primary_field_id = format!("field_{}", primary_field.id);
+    let primary_id_function = generate_primary_id_fn(primary_field);
+    let field_consts = fields.iter().map(|field| {
+        let const_name = format_ident!(
+            "FIELD_{}",
+            field
+                .ident
+                .to_string()
+                .trim_start_matches("r#")
+                .to_uppercase()
+        );
+        let id = field.id;
+        quote! {
+            pub const #const_name: ::baserow_client::schema::FieldId =
+                ::baserow_client::schema::FieldId::new(#id);
+        }
+    });
+
+    let write_impl = (!read_only).then(|| {
+        quote! {
+            impl #impl_generics ::baserow_client::client::BaserowWriteObject for #name #ty_generics #where_clause {
+                type Read = Self;
+            }
+        }
+    });
+    let validate_impl = (!read_only && !custom_validation).then(|| {
+        quote! {
+            impl #impl_generics ::baserow_client::validation::Validate for #name #ty_generics #where_clause {
+                fn validate(&self) -> Vec<::baserow_client::validation::Violation> {
+                    Vec::new()
+                }
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            pub const TABLE_ID: usize = #table_id;
+            #(#field_consts)*
+        }
+
+        impl #impl_generics ::baserow_client::client::BaserowObject for #name #ty_generics #where_clause {
+            fn get_static_table_id() -> usize {
+                Self::TABLE_ID
+            }
+
+            fn get_table_id(&self) -> usize {
+                Self::TABLE_ID
+            }
+
+            fn get_id(&self) -> ::baserow_client::client::Identifier {
+                #primary_id_function
+            }
+
+            fn get_table_id_field(&self) -> String {
+                #primary_field_id.to_string()
+            }
+        }
+
+        #write_impl
+
+        #validate_impl
+    })
+}
+
+fn parse_struct(input: &DeriveInput) -> Result<BaserowStruct, Error> {
+    let mut table_id = None;
+    let mut read_only = false;
+    let mut custom_validation = false;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("baserow"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("table") {
+                table_id = Some(meta.value()?.parse::<LitInt>()?.base10_parse::<usize>()?);
+                Ok(())
+            } else if meta.path.is_ident("read_only") {
+                read_only = true;
+                Ok(())
+            } else if meta.path.is_ident("custom_validation") {
+                custom_validation = true;
+                Ok(())
+            } else {
+                Err(meta.error("expected `table = <id>`, `read_only` or `custom_validation`"))
+            }
+        })?;
+    }
+    let table_id = table_id.ok_or_else(|| {
+        Error::new_spanned(
+            &input.ident,
+            "the table needs to be set with `#[baserow(table = <id>)]`",
+        )
+    })?;
+    Ok(BaserowStruct {
+        table_id,
+        read_only,
+        custom_validation,
+    })
+}
+
+// Fields without a `baserow` attribute are just not known to Baserow, like fields that are
+// skipped by serde
+fn parse_field(field: &Field) -> Result<Option<BaserowField<'_>>, Error> {
+    let mut id = None;
+    let mut primary = false;
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("baserow"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("field") {
+                id = Some(meta.value()?.parse::<LitInt>()?.base10_parse::<usize>()?);
+                Ok(())
+            } else if meta.path.is_ident("primary") {
+                primary = true;
+                Ok(())
+            } else {
+                Err(meta.error("expected `field = <id>` or `primary`"))
+            }
+        })?;
+    }
+
+    match (id, &field.ident) {
+        (Some(id), Some(ident)) => Ok(Some(BaserowField {
+            ident,
+            ty: &field.ty,
+            id,
+            primary,
+        })),
+        (None, _) if primary => Err(Error::new_spanned(
+            field,
+            "the primary field needs an id, like `#[baserow(field = <id>, primary)]`",
+        )),
+        _ => Ok(None),
+    }
+}
+
+// Mirrors what the generator does for primary fields, numbers are passed on as they are and
+// everything else is turned into text
+fn generate_primary_id_fn(field: &BaserowField) -> TokenStream2 {
+    let ident = field.ident;
+    let (ty, optional) = match option_inner_type(field.ty) {
+        Some(inner) => (inner, true),
+        None => (field.ty, false),
+    };
+    let variant = match type_name(ty).as_deref() {
+        Some("isize") => Some(quote! { SignedNumber }),
+        Some("usize") => Some(quote! { UnsignedNumber }),
+        Some("f64") => Some(quote! { FloatNumber }),
+        _ => None,
+    };
+
+    match (variant, optional) {
+        (Some(variant), true) => quote! {
+            ::baserow_client::client::Identifier::#variant { id: self.#ident }
+        },
+        (Some(variant), false) => quote! {
+            ::baserow_client::client::Identifier::#variant { id: Some(self.#ident) }
+        },
+        (None, true) => quote! {
+            ::baserow_client::client::Identifier::Text {
+                id: Some(match &self.#ident {
+                    None => "".to_string(),
+                    Some(name) => name.to_string(),
+                }),
+            }
+        },
+        (None, false) => quote! {
+            ::baserow_client::client::Identifier::Text {
+                id: Some(self.#ident.to_string()),
+            }
+        },
+    }
+}
+
+fn type_name(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(path) if path.qself.is_none() => path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string()),
+        _ => None,
+    }
+}
+
+fn option_inner_type(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(arguments) => match arguments.args.first()? {
+            GenericArgument::Type(inner) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}